@@ -1,4 +1,10 @@
+// The IDL handlers `#[program]` generates in Anchor 0.31 still call `AccountInfo::realloc`,
+// deprecated since solana-account-info 2.3
+#![allow(deprecated)]
+
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::sysvar::slot_hashes;
 use anchor_lang::system_program::{transfer, Transfer};
 
 declare_id!("Game111111111111111111111111111111111111111");
@@ -14,15 +20,33 @@ pub mod gaming_vault {
         first_place_percentage: u8,
         second_place_percentage: u8,
         third_place_percentage: u8,
+        config_timelock_secs: i64,
     ) -> Result<()> {
         require!(fee_bps <= 10000, GameError::InvalidFeeBps);
         require!(
             first_place_percentage + second_place_percentage + third_place_percentage == 100,
             GameError::InvalidPrizeDistribution
         );
+        require!(
+            (0..=MAX_CONFIG_TIMELOCK_SECS).contains(&config_timelock_secs),
+            GameError::InvalidTimelock
+        );
 
         let config = &mut ctx.accounts.game_config;
         config.admin = ctx.accounts.admin.key();
+        config.settlement_authority = ctx.accounts.admin.key();
+        config.pauser = ctx.accounts.admin.key();
+        config.treasury_authority = ctx.accounts.admin.key();
+        config.blocklist_authority = ctx.accounts.admin.key();
+        config.paused = 0;
+        config.outstanding_liabilities = 0;
+        config.max_gas_fee_lamports = DEFAULT_MAX_GAS_FEE_LAMPORTS;
+        config.gas_reimbursement = ctx.accounts.fee_receiver.key();
+        config.max_active_games_per_player = DEFAULT_MAX_ACTIVE_GAMES_PER_PLAYER;
+        config.referral_share_bps = 0;
+        config.fee_tiers = [FeeTier::default(); MAX_FEE_TIERS];
+        config.balance_withdrawal_cooldown_secs = 0;
+        config.reserved = [0; CONFIG_RESERVED_BYTES];
         config.fee_receiver = ctx.accounts.fee_receiver.key();
         config.fee_bps = fee_bps;
         config.first_place_percentage = first_place_percentage;
@@ -30,6 +54,74 @@ pub mod gaming_vault {
         config.third_place_percentage = third_place_percentage;
         config.next_game_id = 1;
         config.bump = ctx.bumps.game_config;
+        config.version = CONFIG_VERSION;
+        config.config_timelock_secs = config_timelock_secs;
+
+        let log = &mut ctx.accounts.admin_action_log;
+        log.next_index = 0;
+        log.total_actions = 0;
+        log.entries = Vec::new();
+        log.bump = ctx.bumps.admin_action_log;
+
+        Ok(())
+    }
+
+    /// Upgrade a config account written by an older program version in place (config authority only)
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        let config_info = ctx.accounts.game_config.to_account_info();
+
+        {
+            let data = config_info.try_borrow_data()?;
+            require!(
+                data.len() >= GameConfig::LEGACY_SIZE && data[..8] == *GameConfig::DISCRIMINATOR,
+                GameError::InvalidConfigAccount
+            );
+            // `admin` sits right after the discriminator in every layout
+            let admin = Pubkey::try_from(&data[8..40]).map_err(|_| GameError::InvalidConfigAccount)?;
+            require!(ctx.accounts.admin.key() == admin, GameError::UnauthorizedAccess);
+        }
+
+        if config_info.data_len() < GameConfig::SIZE {
+            // Top up rent for the larger account before growing it
+            let required = Rent::get()?.minimum_balance(GameConfig::SIZE);
+            let top_up = required.saturating_sub(config_info.lamports());
+            if top_up > 0 {
+                transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        Transfer {
+                            from: ctx.accounts.admin.to_account_info(),
+                            to: config_info.clone(),
+                        },
+                    ),
+                    top_up,
+                )?;
+            }
+            config_info.resize(GameConfig::SIZE)?;
+        }
+
+        // Stakes of games opened before liabilities were tracked are unknown, so everything
+        // in the vault above rent is treated as owed until those games settle
+        let vault_owed = ctx.accounts.vault
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(0));
+
+        let mut data = config_info.try_borrow_mut_data()?;
+        let from_version = migrate_config_data(&mut data, vault_owed)?;
+
+        emit!(ConfigMigrated {
+            from_version,
+            to_version: CONFIG_VERSION,
+        });
+
+        ctx.accounts.admin_action_log.bump = ctx.bumps.admin_action_log;
+        ctx.accounts.admin_action_log.record(
+            ctx.accounts.admin.key(),
+            AdminAction::MigrateConfig,
+            0,
+            from_version as u64,
+            0,
+        )?;
 
         Ok(())
     }
@@ -39,159 +131,367 @@ pub mod gaming_vault {
         ctx: Context<CreateGame>,
         game_type: GameType,
         tier: GameTier,
+        referrer: Option<Pubkey>,
     ) -> Result<()> {
         let config = &mut ctx.accounts.game_config;
         let game = &mut ctx.accounts.game;
 
-        let stake_amount = tier.to_lamports();
-        let fee_amount = (stake_amount as u128 * config.fee_bps as u128 / 10000) as u64;
-        let total_cost = stake_amount + fee_amount;
+        require!(!config.is_paused(PAUSE_CREATE), GameError::Paused);
+        require!(ctx.accounts.blocked.data_is_empty(), GameError::WalletBlocked);
+
+        match game_type {
+            GameType::Tournament { size } => require!(
+                size.is_power_of_two() && (2..=MAX_TOURNAMENT_SIZE as u8).contains(&size),
+                GameError::InvalidTournamentSize
+            ),
+            GameType::DoubleElimination { size, .. } => require!(
+                size.is_power_of_two() && (4..=MAX_TOURNAMENT_SIZE as u8).contains(&size),
+                GameError::InvalidTournamentSize
+            ),
+            GameType::Swiss { size, rounds } => {
+                require!((4..=MAX_SWISS_SIZE as u8).contains(&size), GameError::InvalidTournamentSize);
+                require!(
+                    rounds >= 1 && rounds <= max_swiss_rounds(size as usize),
+                    GameError::InvalidSwissRounds
+                );
+            }
+            GameType::League { size } => require!(
+                (3..=MAX_LEAGUE_SIZE as u8).contains(&size),
+                GameError::InvalidTournamentSize
+            ),
+            GameType::OneVsOne => {}
+        }
 
-        // Validate player has enough funds
+        // Tournaments get their bracket or standings account up front so it can be seeded on auto-start
+        require!(
+            ctx.accounts.bracket.is_some() == game_type.uses_bracket(),
+            GameError::InvalidBracketAccount
+        );
         require!(
-            ctx.accounts.creator.lamports() >= total_cost,
-            GameError::InsufficientFunds
+            ctx.accounts.standings.is_some() == game_type.uses_standings(),
+            GameError::InvalidStandingsAccount
         );
+        if let Some(bracket) = ctx.accounts.bracket.as_mut() {
+            bracket.game_id = config.next_game_id;
+            bracket.bump = ctx.bumps.bracket.unwrap_or_default();
+            bracket.rent_payer = ctx.accounts.payer.key();
+        }
+        if let Some(standings) = ctx.accounts.standings.as_mut() {
+            standings.game_id = config.next_game_id;
+            standings.bump = ctx.bumps.standings.unwrap_or_default();
+            standings.rent_payer = ctx.accounts.payer.key();
+        }
 
-        // Initialize game
-        game.id = config.next_game_id;
-        game.creator = ctx.accounts.creator.key();
-        game.game_type = game_type.clone();
-        game.tier = tier.clone();
-        game.stake_per_player = stake_amount;
-        game.max_players = match game_type {
-            GameType::OneVsOne => 2,
-            GameType::Tournament { size } => size,
-        };
-        game.status = GameStatus::WaitingForPlayers;
-        game.players = vec![PlayerStake {
-            player: ctx.accounts.creator.key(),
-            amount: stake_amount,
-            joined_at: Clock::get()?.unix_timestamp,
-        }];
-        game.total_pot = stake_amount;
-        game.created_at = Clock::get()?.unix_timestamp;
-        game.bump = ctx.bumps.game;
+        open_game(
+            config,
+            game,
+            ctx.bumps.game,
+            game_type,
+            tier,
+            Entrant {
+                player: ctx.accounts.creator.key(),
+                profile: &mut ctx.accounts.creator_profile,
+                profile_bump: ctx.bumps.creator_profile,
+                referrer,
+                referrer_profile: ctx.accounts.referrer_profile.as_deref_mut(),
+                payer: &ctx.accounts.payer,
+                player_balance: ctx.accounts.player_balance.as_ref(),
+                vault: &ctx.accounts.vault,
+                fee_receiver: &ctx.accounts.fee_receiver,
+                system_program: &ctx.accounts.system_program,
+            },
+        )
+    }
 
-        // Transfer stake and fee
-        transfer(
-            CpiContext::new(
-                ctx.accounts.system_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.creator.to_account_info(),
-                    to: ctx.accounts.vault.to_account_info(),
-                },
-            ),
-            stake_amount,
-        )?;
+    /// Join an existing game
+    pub fn join_game(ctx: Context<JoinGame>, game_id: u64, referrer: Option<Pubkey>) -> Result<()> {
+        let config = &mut ctx.accounts.game_config;
+        let game = &mut ctx.accounts.game;
 
-        transfer(
-            CpiContext::new(
-                ctx.accounts.system_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.creator.to_account_info(),
-                    to: ctx.accounts.fee_receiver.to_account_info(),
-                },
-            ),
-            fee_amount,
+        require!(!config.is_paused(PAUSE_JOIN), GameError::Paused);
+        require!(ctx.accounts.blocked.data_is_empty(), GameError::WalletBlocked);
+
+        // Validate game state
+        require!(game.id == game_id, GameError::InvalidGameId);
+        require!(game.status == GameStatus::WaitingForPlayers, GameError::GameNotJoinable);
+        require!(game.players.len() < game.max_players as usize, GameError::GameFull);
+
+        // Check if player is already in this specific game
+        require!(
+            !game.players.iter().any(|p| p.player == ctx.accounts.player.key()),
+            GameError::AlreadyInThisGame
+        );
+        require!(
+            game.seat_open_to(&ctx.accounts.player.key(), Clock::get()?.unix_timestamp),
+            GameError::GameReserved
+        );
+
+        // Backend handles all other eligibility checks (eliminations, etc.)
+
+        let terms = take_seat(
+            config,
+            game,
+            Entrant {
+                player: ctx.accounts.player.key(),
+                profile: &mut ctx.accounts.player_profile,
+                profile_bump: ctx.bumps.player_profile,
+                referrer,
+                referrer_profile: ctx.accounts.referrer_profile.as_deref_mut(),
+                payer: &ctx.accounts.payer,
+                player_balance: ctx.accounts.player_balance.as_ref(),
+                vault: &ctx.accounts.vault,
+                fee_receiver: &ctx.accounts.fee_receiver,
+                system_program: &ctx.accounts.system_program,
+            },
         )?;
 
         // No need to update player state - backend handles this
 
-        // Increment next game ID
-        config.next_game_id += 1;
+        // Auto-start game if full
+        if game.players.len() == game.max_players as usize {
+            start_game(
+                game,
+                ctx.accounts.bracket.as_deref_mut(),
+                ctx.accounts.standings.as_deref_mut(),
+                &ctx.accounts.slot_hashes,
+            )?;
+        }
 
-        emit!(GameCreated {
+        emit!(PlayerJoined {
             game_id: game.id,
-            creator: ctx.accounts.creator.key(),
-            game_type: game_type,
-            tier: tier,
-            stake_amount,
+            player: ctx.accounts.player.key(),
+            payer: terms.payer,
+            players_count: game.players.len() as u8,
+            fee_bps: terms.fee_bps,
         });
 
         Ok(())
     }
 
-    /// Join an existing game
-    pub fn join_game(ctx: Context<JoinGame>, game_id: u64) -> Result<()> {
-        let config = &ctx.accounts.game_config;
+    /// Open a 1v1 at the same tier as a completed 1v1, with the second seat reserved for the
+    /// previous opponent. Either player of the previous game can call it, once per game.
+    pub fn create_rematch(ctx: Context<CreateRematch>, prev_game_id: u64) -> Result<()> {
+        let config = &mut ctx.accounts.game_config;
+        let previous_game = &mut ctx.accounts.previous_game;
         let game = &mut ctx.accounts.game;
 
-        // Validate game state
-        require!(game.id == game_id, GameError::InvalidGameId);
-        require!(game.status == GameStatus::WaitingForPlayers, GameError::GameNotJoinable);
-        require!(game.players.len() < game.max_players as usize, GameError::GameFull);
+        require!(!config.is_paused(PAUSE_CREATE), GameError::Paused);
+        require!(ctx.accounts.blocked.data_is_empty(), GameError::WalletBlocked);
 
-        // Check if player is already in this specific game
+        require!(previous_game.id == prev_game_id, GameError::InvalidGameId);
         require!(
-            !game.players.iter().any(|p| p.player == ctx.accounts.player.key()),
-            GameError::AlreadyInThisGame
+            previous_game.game_type == GameType::OneVsOne
+                && previous_game.status == GameStatus::Completed
+                && previous_game.rematch_game_id == 0,
+            GameError::RematchNotAvailable
         );
+        require!(
+            previous_game.players.iter().any(|p| p.player == ctx.accounts.creator.key()),
+            GameError::RematchNotAvailable
+        );
+        let opponent = previous_game
+            .players
+            .iter()
+            .map(|p| p.player)
+            .find(|player| *player != ctx.accounts.creator.key())
+            .ok_or(GameError::RematchNotAvailable)?;
+
+        open_game(
+            config,
+            game,
+            ctx.bumps.game,
+            GameType::OneVsOne,
+            previous_game.tier.clone(),
+            Entrant {
+                player: ctx.accounts.creator.key(),
+                profile: &mut ctx.accounts.creator_profile,
+                profile_bump: ctx.bumps.creator_profile,
+                referrer: None,
+                referrer_profile: ctx.accounts.referrer_profile.as_deref_mut(),
+                payer: &ctx.accounts.payer,
+                player_balance: ctx.accounts.player_balance.as_ref(),
+                vault: &ctx.accounts.vault,
+                fee_receiver: &ctx.accounts.fee_receiver,
+                system_program: &ctx.accounts.system_program,
+            },
+        )?;
+        game.reserved_for = opponent;
+        game.previous_game_id = previous_game.id;
+        previous_game.rematch_game_id = game.id;
+
+        emit!(RematchCreated {
+            game_id: game.id,
+            previous_game_id: previous_game.id,
+            creator: ctx.accounts.creator.key(),
+            opponent,
+        });
+
+        Ok(())
+    }
+
+    /// Pay the caller's accrued referral rewards out of the vault
+    pub fn claim_referral_rewards(ctx: Context<ClaimReferralRewards>) -> Result<()> {
+        let profile = &mut ctx.accounts.referrer_profile;
+        let amount = profile.referral_rewards;
+        require!(amount > 0, GameError::NoReferralRewards);
+
+        profile.referral_rewards = 0;
+        ctx.accounts.game_config.release_liability(amount);
+
+        **ctx.accounts.vault.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.referrer.to_account_info().try_borrow_mut_lamports()? += amount;
 
-        // Backend handles all other eligibility checks (active games, eliminations, etc.)
+        emit!(ReferralRewardsClaimed {
+            referrer: ctx.accounts.referrer.key(),
+            amount,
+        });
+
+        Ok(())
+    }
 
-        let stake_amount = game.stake_per_player;
-        let fee_amount = (stake_amount as u128 * config.fee_bps as u128 / 10000) as u64;
-        let total_cost = stake_amount + fee_amount;
+    /// Let a short-lived key make gameplay moves for the player in the given games.
+    /// Replaces any earlier session key; nothing that moves funds accepts it.
+    pub fn register_session_key(
+        ctx: Context<RegisterSessionKey>,
+        session_key: Pubkey,
+        game_ids: Vec<u64>,
+        expires_in_secs: i64,
+    ) -> Result<()> {
+        let player = ctx.accounts.player.key();
 
-        // Validate player has enough funds
         require!(
-            ctx.accounts.player.lamports() >= total_cost,
-            GameError::InsufficientFunds
+            session_key != Pubkey::default() && session_key != player,
+            GameError::InvalidSessionKey
+        );
+        require!(
+            !game_ids.is_empty() && game_ids.len() <= MAX_SESSION_GAMES && !game_ids.contains(&0),
+            GameError::InvalidSessionKey
         );
+        require!(
+            (1..=MAX_SESSION_LIFETIME_SECS).contains(&expires_in_secs),
+            GameError::InvalidSessionKey
+        );
+
+        let session = &mut ctx.accounts.session;
+        session.player = player;
+        session.session_key = session_key;
+        session.game_ids = [0; MAX_SESSION_GAMES];
+        session.game_ids[..game_ids.len()].copy_from_slice(&game_ids);
+        session.expires_at = Clock::get()?.unix_timestamp + expires_in_secs;
+        session.bump = ctx.bumps.session;
+
+        emit!(SessionKeyRegistered {
+            player,
+            session_key,
+            game_ids,
+            expires_at: session.expires_at,
+        });
+
+        Ok(())
+    }
 
-        // Add player to game
-        game.players.push(PlayerStake {
+    /// Drop the player's session key before it expires and return its rent
+    pub fn revoke_session_key(ctx: Context<RevokeSessionKey>) -> Result<()> {
+        emit!(SessionKeyRevoked {
             player: ctx.accounts.player.key(),
-            amount: stake_amount,
-            joined_at: Clock::get()?.unix_timestamp,
+            session_key: ctx.accounts.session.session_key,
         });
-        game.total_pot += stake_amount;
 
-        // Transfer stake and fee
-        transfer(
-            CpiContext::new(
-                ctx.accounts.system_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.player.to_account_info(),
-                    to: ctx.accounts.vault.to_account_info(),
-                },
-            ),
-            stake_amount,
-        )?;
+        Ok(())
+    }
+
+    /// Move lamports from the player's wallet into their internal balance
+    pub fn deposit_balance(ctx: Context<DepositBalance>, amount: u64) -> Result<()> {
+        require!(!ctx.accounts.game_config.is_paused(PAUSE_DEPOSITS), GameError::Paused);
+        require!(ctx.accounts.blocked.data_is_empty(), GameError::WalletBlocked);
+
+        let balance = &mut ctx.accounts.player_balance;
+        balance.player = ctx.accounts.player.key();
+        balance.last_deposit_at = Clock::get()?.unix_timestamp;
+        balance.bump = ctx.bumps.player_balance;
 
         transfer(
             CpiContext::new(
                 ctx.accounts.system_program.to_account_info(),
                 Transfer {
                     from: ctx.accounts.player.to_account_info(),
-                    to: ctx.accounts.fee_receiver.to_account_info(),
+                    to: ctx.accounts.player_balance.to_account_info(),
                 },
             ),
-            fee_amount,
+            amount,
         )?;
 
-        // No need to update player state - backend handles this
+        emit!(BalanceDeposited {
+            player: ctx.accounts.player.key(),
+            amount,
+            balance: balance_available(&ctx.accounts.player_balance)?,
+        });
 
-        // Auto-start game if full
-        if game.players.len() == game.max_players as usize {
-            game.status = GameStatus::Active;
-            
-            emit!(GameStarted {
-                game_id: game.id,
-                players: game.players.iter().map(|p| p.player).collect(),
-            });
-        }
+        Ok(())
+    }
 
-        emit!(PlayerJoined {
-            game_id: game.id,
+    /// Move lamports from the player's internal balance back to their wallet, once the
+    /// configured cooldown since the last deposit has passed
+    pub fn withdraw_balance(ctx: Context<WithdrawBalance>, amount: u64) -> Result<()> {
+        let config = &ctx.accounts.game_config;
+        let balance = &ctx.accounts.player_balance;
+
+        balance.check_cooldown(config.balance_withdrawal_cooldown_secs, Clock::get()?.unix_timestamp)?;
+        require!(balance_available(balance)? >= amount, GameError::InsufficientFunds);
+
+        **balance.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.player.to_account_info().try_borrow_mut_lamports()? += amount;
+
+        emit!(BalanceWithdrawn {
             player: ctx.accounts.player.key(),
-            players_count: game.players.len() as u8,
+            amount,
+            balance: balance_available(balance)?,
         });
 
         Ok(())
     }
 
+    /// Admin function to start a tournament before it fills; missing seats become byes, except in
+    /// double elimination, which only starts early on a smaller power-of-two field
+    pub fn admin_start_game(ctx: Context<AdminStartGame>, game_id: u64) -> Result<()> {
+        let config = &ctx.accounts.game_config;
+        let game = &mut ctx.accounts.game;
+
+        // Validate settlement operator
+        require!(ctx.accounts.admin.key() == config.settlement_authority, GameError::UnauthorizedAccess);
+        require!(game.id == game_id, GameError::InvalidGameId);
+        require!(game.status == GameStatus::WaitingForPlayers, GameError::GameAlreadyStarted);
+        require!(
+            game.game_type.uses_bracket() || game.game_type.uses_standings(),
+            GameError::InvalidBracketAccount
+        );
+        require!(game.players.len() >= 2, GameError::NotEnoughPlayers);
+        if let GameType::DoubleElimination { .. } = game.game_type {
+            let entrants = game.players.len();
+            require!(
+                entrants.is_power_of_two() && entrants >= 4,
+                GameError::DoubleEliminationByes
+            );
+        }
+
+        start_game(
+            game,
+            ctx.accounts.bracket.as_deref_mut(),
+            ctx.accounts.standings.as_deref_mut(),
+            &ctx.accounts.slot_hashes,
+        )?;
+
+        ctx.accounts.admin_action_log.record(
+            ctx.accounts.admin.key(),
+            AdminAction::StartGame,
+            game_id,
+            game.total_pot,
+            0,
+        )?;
+
+        Ok(())
+    }
+
     /// Admin function to withdraw a player before game starts
     pub fn admin_withdraw_player(
         ctx: Context<AdminWithdrawPlayer>,
@@ -199,13 +499,14 @@ pub mod gaming_vault {
         player_pubkey: Pubkey,
         gas_fee: u64,
     ) -> Result<()> {
-        let config = &ctx.accounts.game_config;
+        let config = &mut ctx.accounts.game_config;
         let game = &mut ctx.accounts.game;
 
-        // Validate admin
-        require!(ctx.accounts.admin.key() == config.admin, GameError::UnauthorizedAccess);
+        // Validate settlement operator
+        require!(ctx.accounts.admin.key() == config.settlement_authority, GameError::UnauthorizedAccess);
         require!(game.id == game_id, GameError::InvalidGameId);
         require!(game.status == GameStatus::WaitingForPlayers, GameError::GameAlreadyStarted);
+        require!(gas_fee <= config.max_gas_fee_lamports, GameError::GasFeeTooHigh);
 
         // Find and remove player
         let player_index = game.players
@@ -213,30 +514,47 @@ pub mod gaming_vault {
             .position(|p| p.player == player_pubkey)
             .ok_or(GameError::PlayerNotInGame)?;
 
-        let player_stake = game.players.remove(player_index);
+        let (player_stake, terms) = game.remove_player(player_index);
         game.total_pot -= player_stake.amount;
+        config.release_liability(player_stake.amount);
 
         // Calculate refund amount (stake minus gas fee)
         let refund_amount = player_stake.amount.saturating_sub(gas_fee);
+        let gas_deducted = player_stake.amount - refund_amount;
 
-        // Find player account in remaining accounts
+        // Refunds go to whoever paid the stake
         let player_account = ctx.remaining_accounts
             .iter()
-            .find(|acc| acc.key() == player_pubkey)
+            .find(|acc| acc.key() == terms.payer)
             .ok_or(GameError::MissingPlayerAccount)?;
 
         // Transfer refund from vault
         **ctx.accounts.vault.to_account_info().try_borrow_mut_lamports()? -= refund_amount;
         **player_account.try_borrow_mut_lamports()? += refund_amount;
 
-        // No need to update player state - backend handles eligibility
+        reimburse_gas(game, &ctx.accounts.vault, &ctx.accounts.gas_reimbursement, gas_deducted)?;
+
+        update_profile(&ctx.accounts.player_profile, &player_pubkey, |profile| profile.close_game())?;
 
         emit!(PlayerWithdrawn {
             game_id: game.id,
             player: player_pubkey,
+            refunded_to: terms.payer,
             refund_amount,
+            gas_deducted,
+            fee_paid: terms.fee_paid(player_stake.amount),
+            admin: ctx.accounts.admin.key(),
+            remaining_players: game.players.len() as u8,
         });
 
+        ctx.accounts.admin_action_log.record(
+            ctx.accounts.admin.key(),
+            AdminAction::WithdrawPlayer,
+            game_id,
+            refund_amount,
+            gas_deducted,
+        )?;
+
         Ok(())
     }
 
@@ -246,36 +564,79 @@ pub mod gaming_vault {
         game_id: u64,
         gas_fee_per_player: u64,
     ) -> Result<()> {
-        let config = &ctx.accounts.game_config;
+        let config = &mut ctx.accounts.game_config;
         let game = &mut ctx.accounts.game;
 
-        // Validate admin
-        require!(ctx.accounts.admin.key() == config.admin, GameError::UnauthorizedAccess);
+        // Validate settlement operator
+        require!(ctx.accounts.admin.key() == config.settlement_authority, GameError::UnauthorizedAccess);
         require!(game.id == game_id, GameError::InvalidGameId);
-        require!(game.status != GameStatus::Cancelled, GameError::GameAlreadyCancelled);
+        // Completed games were paid out and their liability released; refunding them would pay twice
+        match game.status {
+            GameStatus::WaitingForPlayers | GameStatus::Active => {}
+            GameStatus::Completed => return Err(GameError::GameAlreadyCompleted.into()),
+            GameStatus::Cancelled => return Err(GameError::GameAlreadyCancelled.into()),
+        }
+        require!(gas_fee_per_player <= config.max_gas_fee_lamports, GameError::GasFeeTooHigh);
+        // The bracket or standings are closed along with the game, returning their rent
+        require!(
+            ctx.accounts.bracket.is_some() == game.game_type.uses_bracket(),
+            GameError::InvalidBracketAccount
+        );
+        require!(
+            ctx.accounts.standings.is_some() == game.game_type.uses_standings(),
+            GameError::InvalidStandingsAccount
+        );
 
         // Refund all players
+        let mut gas_deducted = 0;
+        let mut total_refunded = 0;
         for (i, player_stake) in game.players.iter().enumerate() {
             let refund_amount = player_stake.amount.saturating_sub(gas_fee_per_player);
+            gas_deducted += player_stake.amount - refund_amount;
+            total_refunded += refund_amount;
             
+            // Refunds go to whoever paid the stake
             let player_account = ctx.remaining_accounts
-                .get(i) // Just player accounts, no player state accounts needed
+                .get(i)
                 .ok_or(GameError::MissingPlayerAccount)?;
+            require!(player_account.key() == game.terms(i).payer, GameError::RefundAccountMismatch);
 
             // Transfer refund
             **ctx.accounts.vault.to_account_info().try_borrow_mut_lamports()? -= refund_amount;
             **player_account.try_borrow_mut_lamports()? += refund_amount;
         }
 
-        // No need to update player states - backend handles eligibility
+        reimburse_gas(game, &ctx.accounts.vault, &ctx.accounts.gas_reimbursement, gas_deducted)?;
 
         game.status = GameStatus::Cancelled;
+        config.release_liability(game.total_pot);
+
+        // A cancelled rematch frees the previous game to be rematched again
+        if game.previous_game_id != 0 {
+            let previous_game = ctx.accounts.previous_game
+                .as_mut()
+                .ok_or(GameError::MissingPreviousGame)?;
+            if previous_game.rematch_game_id == game.id {
+                previous_game.rematch_game_id = 0;
+            }
+        }
 
         emit!(GameCancelled {
             game_id: game.id,
             refunded_players: game.players.len() as u8,
+            gas_deducted,
+            total_refunded,
+            admin: ctx.accounts.admin.key(),
         });
 
+        ctx.accounts.admin_action_log.record(
+            ctx.accounts.admin.key(),
+            AdminAction::CancelGame,
+            game_id,
+            total_refunded,
+            gas_deducted,
+        )?;
+
         Ok(())
     }
 
@@ -286,50 +647,260 @@ pub mod gaming_vault {
         winners: Vec<WinnerPayout>,
         gas_fee_per_winner: u64,
     ) -> Result<()> {
-        let config = &ctx.accounts.game_config;
+        let config = &mut ctx.accounts.game_config;
         let game = &mut ctx.accounts.game;
 
-        // Validate admin
-        require!(ctx.accounts.admin.key() == config.admin, GameError::UnauthorizedAccess);
+        // Validate settlement operator
+        require!(ctx.accounts.admin.key() == config.settlement_authority, GameError::UnauthorizedAccess);
+        require!(!config.is_paused(PAUSE_SETTLEMENT), GameError::Paused);
+        require!(gas_fee_per_winner <= config.max_gas_fee_lamports, GameError::GasFeeTooHigh);
         require!(game.id == game_id, GameError::InvalidGameId);
         require!(game.status == GameStatus::Active, GameError::GameNotActive);
 
         let total_prize_pool = game.total_pot;
 
+        // The whole 1v1 pot goes to a single player of this game
+        if game.game_type == GameType::OneVsOne {
+            require!(
+                winners.len() == 1 && game.players.iter().any(|p| p.player == winners[0].player),
+                GameError::PlacementMismatch
+            );
+        }
+
+        // Tournament placements must match the bracket or standings that were actually played
+        if game.game_type.uses_bracket() {
+            let bracket = ctx.accounts.bracket
+                .as_ref()
+                .ok_or(GameError::InvalidBracketAccount)?;
+            require!(bracket.completed, GameError::BracketNotCompleted);
+            check_placements(&bracket.placements, &winners)?;
+        }
+        if game.game_type.uses_standings() {
+            let standings = ctx.accounts.standings
+                .as_ref()
+                .ok_or(GameError::InvalidStandingsAccount)?;
+            require!(standings.completed, GameError::StandingsNotFinal);
+            check_placements(&standings.placements, &winners)?;
+        }
+
         // Validate and payout winners
+        let mut gas_deducted = 0;
+        let mut total_paid = 0;
+        let mut payouts = Vec::with_capacity(winners.len());
         for (i, winner) in winners.iter().enumerate() {
             let prize_amount = match game.game_type {
                 GameType::OneVsOne => total_prize_pool, // Winner takes all
-                GameType::Tournament { .. } => {
-                    match winner.place {
-                        1 => (total_prize_pool as u128 * config.first_place_percentage as u128 / 100) as u64,
-                        2 => (total_prize_pool as u128 * config.second_place_percentage as u128 / 100) as u64,
-                        3 => (total_prize_pool as u128 * config.third_place_percentage as u128 / 100) as u64,
-                        _ => return Err(GameError::InvalidPlace.into()),
-                    }
+                GameType::Tournament { .. }
+                | GameType::DoubleElimination { .. }
+                | GameType::Swiss { .. }
+                | GameType::League { .. } => {
+                    let percentage = config.place_percentage(winner.place, winners.len())?;
+                    (total_prize_pool as u128 * percentage as u128 / 100) as u64
                 }
             };
 
             let payout_amount = prize_amount.saturating_sub(gas_fee_per_winner);
+            gas_deducted += prize_amount - payout_amount;
+            total_paid += payout_amount;
 
             let winner_account = ctx.remaining_accounts
-                .get(i) // Just winner accounts, no player state accounts needed
+                .get(i)
                 .ok_or(GameError::MissingWinnerAccount)?;
+            require!(
+                winner_account.key() == game.payout_account(&winner.player),
+                GameError::WinnerAccountMismatch
+            );
+            payouts.push(Prize {
+                player: winner.player,
+                place: winner.place,
+                amount: payout_amount,
+            });
 
             // Transfer prize
             **ctx.accounts.vault.to_account_info().try_borrow_mut_lamports()? -= payout_amount;
             **winner_account.try_borrow_mut_lamports()? += payout_amount;
         }
 
-        // No need to update player states - backend handles eligibility
+        reimburse_gas(game, &ctx.accounts.vault, &ctx.accounts.gas_reimbursement, gas_deducted)?;
 
+        // Profiles pick these up through `release_active_game`
+        game.prizes = payouts;
         game.status = GameStatus::Completed;
+        config.release_liability(game.total_pot);
 
         emit!(GameCompleted {
             game_id: game.id,
             winners: winners.clone(),
+            gas_deducted,
+            total_paid,
+            admin: ctx.accounts.admin.key(),
+        });
+
+        ctx.accounts.admin_action_log.record(
+            ctx.accounts.admin.key(),
+            AdminAction::PayoutWinners,
+            game_id,
+            total_paid,
+            gas_deducted,
+        )?;
+
+        Ok(())
+    }
+
+    /// Admin function to record a bracket match result
+    pub fn advance_winner(
+        ctx: Context<AdvanceWinner>,
+        game_id: u64,
+        match_index: u8,
+        winner: Pubkey,
+    ) -> Result<()> {
+        let config = &ctx.accounts.game_config;
+        let game = &ctx.accounts.game;
+        let bracket = &mut ctx.accounts.bracket;
+
+        // Validate settlement operator
+        require!(ctx.accounts.admin.key() == config.settlement_authority, GameError::UnauthorizedAccess);
+        require!(game.id == game_id, GameError::InvalidGameId);
+        require!(game.status == GameStatus::Active, GameError::GameNotActive);
+
+        let loser = bracket.advance(match_index, winner)?;
+
+        emit!(MatchAdvanced {
+            game_id,
+            match_index,
+            winner,
+            loser,
+        });
+
+        if bracket.completed {
+            emit!(BracketCompleted {
+                game_id,
+                placements: bracket.placements.iter().flatten().copied().collect(),
+            });
+        }
+
+        ctx.accounts.admin_action_log.record(
+            ctx.accounts.admin.key(),
+            AdminAction::AdvanceWinner,
+            game_id,
+            0,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Admin function to record the result of a Swiss pairing; the next round is paired
+    /// automatically once every result of the current round is in
+    pub fn record_swiss_result(
+        ctx: Context<RecordSwissResult>,
+        game_id: u64,
+        pairing_index: u8,
+        winner: Pubkey,
+    ) -> Result<()> {
+        let config = &ctx.accounts.game_config;
+        let game = &ctx.accounts.game;
+        let standings = &mut ctx.accounts.standings;
+
+        // Validate settlement operator
+        require!(ctx.accounts.admin.key() == config.settlement_authority, GameError::UnauthorizedAccess);
+        require!(game.id == game_id, GameError::InvalidGameId);
+        require!(
+            matches!(game.game_type, GameType::Swiss { .. }),
+            GameError::InvalidStandingsAccount
+        );
+        require!(game.status == GameStatus::Active, GameError::GameNotActive);
+
+        let round = standings.round;
+        let loser = standings.record_swiss_result(pairing_index, winner)?;
+
+        emit!(SwissResultRecorded {
+            game_id,
+            round,
+            pairing_index,
+            winner,
+            loser,
+        });
+
+        if standings.completed {
+            emit!(StandingsFinalized {
+                game_id,
+                placements: standings.placements.iter().flatten().copied().collect(),
+            });
+        } else if standings.round != round {
+            emit!(SwissRoundPaired {
+                game_id,
+                round: standings.round,
+                pairings: standings.pairings.clone(),
+            });
+        }
+
+        ctx.accounts.admin_action_log.record(
+            ctx.accounts.admin.key(),
+            AdminAction::RecordSwissResult,
+            game_id,
+            0,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Admin function to record a league pairing; each pairing gets its own match account,
+    /// so a result can only be recorded once
+    pub fn record_league_result(
+        ctx: Context<RecordLeagueResult>,
+        game_id: u64,
+        a: u8,
+        b: u8,
+        winner: Pubkey,
+    ) -> Result<()> {
+        let config = &ctx.accounts.game_config;
+        let game = &ctx.accounts.game;
+        let standings = &mut ctx.accounts.standings;
+        let league_match = &mut ctx.accounts.league_match;
+
+        // Validate settlement operator
+        require!(ctx.accounts.admin.key() == config.settlement_authority, GameError::UnauthorizedAccess);
+        require!(game.id == game_id, GameError::InvalidGameId);
+        require!(game.status == GameStatus::Active, GameError::GameNotActive);
+        require!(
+            matches!(game.game_type, GameType::League { .. }),
+            GameError::InvalidStandingsAccount
+        );
+
+        let loser = standings.record_league_result(a, b, winner)?;
+
+        league_match.game_id = game_id;
+        league_match.player_a = standings.entries[a as usize].player;
+        league_match.player_b = standings.entries[b as usize].player;
+        league_match.winner = winner;
+        league_match.recorded_slot = Clock::get()?.slot;
+        league_match.bump = ctx.bumps.league_match;
+
+        emit!(LeagueResultRecorded {
+            game_id,
+            a,
+            b,
+            winner,
+            loser,
         });
 
+        if standings.completed {
+            emit!(StandingsFinalized {
+                game_id,
+                placements: standings.placements.iter().flatten().copied().collect(),
+            });
+        }
+
+        ctx.accounts.admin_action_log.record(
+            ctx.accounts.admin.key(),
+            AdminAction::RecordLeagueResult,
+            game_id,
+            0,
+            0,
+        )?;
+
         Ok(())
     }
 
@@ -341,10 +912,15 @@ pub mod gaming_vault {
     ) -> Result<()> {
         let config = &ctx.accounts.game_config;
 
-        // Validate admin
-        require!(ctx.accounts.admin.key() == config.admin, GameError::UnauthorizedAccess);
+        // Validate treasury authority
+        require!(ctx.accounts.admin.key() == config.treasury_authority, GameError::UnauthorizedAccess);
+        require!(!config.is_paused(PAUSE_WITHDRAWALS), GameError::Paused);
         require!(ctx.accounts.vault.lamports() >= amount, GameError::InsufficientVaultBalance);
 
+        // Only the surplus above player stakes and rent can leave through this path
+        let surplus = vault_surplus(&ctx.accounts.vault, config)?;
+        require!(amount <= surplus, GameError::ExceedsVaultSurplus);
+
         let recipient_account = ctx.remaining_accounts
             .iter()
             .find(|acc| acc.key() == recipient)
@@ -359,276 +935,3617 @@ pub mod gaming_vault {
             amount,
         });
 
-        Ok(())
-    }
+        ctx.accounts.admin_action_log.record(
+            ctx.accounts.admin.key(),
+            AdminAction::EmergencyWithdraw,
+            0,
+            amount,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Propose a new key for a role; takes effect only once that key accepts.
+    /// The config authority can propose for any role, a role holder for its own role.
+    pub fn propose_authority(
+        ctx: Context<ProposeAuthority>,
+        role: AdminRole,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        let config = &ctx.accounts.game_config;
+        let proposal = &mut ctx.accounts.proposal;
+        let proposer = ctx.accounts.proposer.key();
+
+        require!(
+            proposer == config.admin || proposer == config.role_authority(&role),
+            GameError::UnauthorizedAccess
+        );
+
+        proposal.role = role.clone();
+        proposal.proposer = proposer;
+        proposal.new_authority = new_authority;
+        proposal.proposed_at = Clock::get()?.unix_timestamp;
+        proposal.bump = ctx.bumps.proposal;
+
+        emit!(AuthorityProposed {
+            role,
+            proposer,
+            new_authority,
+        });
+
+        ctx.accounts.admin_action_log.record(
+            proposer,
+            AdminAction::ProposeAuthority,
+            0,
+            0,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Accept a pending role proposal; must be signed by the proposed key
+    pub fn accept_authority(ctx: Context<AcceptAuthority>, role: AdminRole) -> Result<()> {
+        let config = &mut ctx.accounts.game_config;
+        let proposal = &ctx.accounts.proposal;
+
+        require!(
+            ctx.accounts.new_authority.key() == proposal.new_authority,
+            GameError::UnauthorizedAccess
+        );
+
+        let previous = config.role_authority(&role);
+        config.set_role_authority(&role, proposal.new_authority);
+
+        emit!(RoleUpdated {
+            role,
+            previous,
+            new_authority: proposal.new_authority,
+        });
+
+        ctx.accounts.admin_action_log.record(
+            proposal.new_authority,
+            AdminAction::AcceptAuthority,
+            0,
+            0,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Withdraw a pending role proposal (proposer, config authority or the proposed key)
+    pub fn cancel_authority_proposal(ctx: Context<CancelAuthorityProposal>, role: AdminRole) -> Result<()> {
+        let config = &ctx.accounts.game_config;
+        let proposal = &ctx.accounts.proposal;
+        let signer = ctx.accounts.signer.key();
+
+        require!(
+            signer == proposal.proposer || signer == proposal.new_authority || signer == config.admin,
+            GameError::UnauthorizedAccess
+        );
+
+        emit!(AuthorityProposalCancelled {
+            role,
+            cancelled_by: signer,
+            new_authority: proposal.new_authority,
+        });
+
+        ctx.accounts.admin_action_log.record(
+            signer,
+            AdminAction::CancelAuthorityProposal,
+            0,
+            0,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Set the pause flags (pauser only). Refunds and cancellations are never paused.
+    pub fn set_paused(ctx: Context<SetPaused>, paused: u8) -> Result<()> {
+        let config = &mut ctx.accounts.game_config;
+
+        require!(ctx.accounts.admin.key() == config.pauser, GameError::UnauthorizedAccess);
+        require!(paused & !PAUSE_ALL == 0, GameError::InvalidPauseFlags);
+
+        let previous = config.paused;
+        config.paused = paused;
+
+        emit!(PauseUpdated { previous, paused });
+
+        ctx.accounts.admin_action_log.record(
+            ctx.accounts.admin.key(),
+            AdminAction::SetPaused,
+            0,
+            paused as u64,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Stop a wallet from creating or joining games (blocklist authority only).
+    /// Games it is already in still refund and pay out normally.
+    pub fn block_wallet(ctx: Context<BlockWallet>, wallet: Pubkey) -> Result<()> {
+        let config = &ctx.accounts.game_config;
+        let blocked = &mut ctx.accounts.blocked;
+
+        require!(ctx.accounts.admin.key() == config.blocklist_authority, GameError::UnauthorizedAccess);
+
+        blocked.wallet = wallet;
+        blocked.blocked_by = ctx.accounts.admin.key();
+        blocked.blocked_at = Clock::get()?.unix_timestamp;
+        blocked.bump = ctx.bumps.blocked;
+
+        emit!(WalletBlocked {
+            wallet,
+            blocked_by: ctx.accounts.admin.key(),
+        });
+
+        ctx.accounts.admin_action_log.record(
+            ctx.accounts.admin.key(),
+            AdminAction::BlockWallet,
+            0,
+            0,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Lift a block; the rent goes back to whoever created it (blocklist authority only)
+    pub fn unblock_wallet(ctx: Context<UnblockWallet>, wallet: Pubkey) -> Result<()> {
+        let config = &ctx.accounts.game_config;
+
+        require!(ctx.accounts.admin.key() == config.blocklist_authority, GameError::UnauthorizedAccess);
+
+        emit!(WalletUnblocked {
+            wallet,
+            unblocked_by: ctx.accounts.admin.key(),
+        });
+
+        ctx.accounts.admin_action_log.record(
+            ctx.accounts.admin.key(),
+            AdminAction::UnblockWallet,
+            0,
+            0,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Recount a player's open games (settlement authority only). Every waiting or active game
+    /// the player is in, and every settled one they have not released yet, must be passed as a
+    /// remaining account; fixes counters left stale by games that settled before the counter existed.
+    pub fn resync_active_games(ctx: Context<ResyncActiveGames>, player_pubkey: Pubkey) -> Result<()> {
+        let config = &ctx.accounts.game_config;
+
+        require!(ctx.accounts.admin.key() == config.settlement_authority, GameError::UnauthorizedAccess);
+
+        let mut seen: Vec<Pubkey> = Vec::new();
+        for info in ctx.remaining_accounts {
+            require!(!seen.contains(info.key), GameError::DuplicateGameAccount);
+            seen.push(info.key());
+
+            require!(info.owner == &crate::ID, GameError::InvalidGameId);
+            let game = Game::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+            let index = game.players
+                .iter()
+                .position(|p| p.player == player_pubkey)
+                .ok_or(GameError::PlayerNotInGame)?;
+            // A settled game keeps its slot until `release_active_game` frees it
+            let holds_slot = match game.status {
+                GameStatus::WaitingForPlayers | GameStatus::Active => true,
+                _ => game.profiles_released & (1 << index) == 0,
+            };
+            require!(holds_slot, GameError::GameNotOpen);
+        }
+
+        let profile = &mut ctx.accounts.player_profile;
+        let previous = profile.active_games;
+        profile.active_games = seen.len() as u16;
+
+        emit!(ActiveGamesResynced {
+            player: player_pubkey,
+            previous,
+            active_games: profile.active_games,
+        });
+
+        ctx.accounts.admin_action_log.record(
+            ctx.accounts.admin.key(),
+            AdminAction::ResyncActiveGames,
+            0,
+            profile.active_games as u64,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Release one player's slot in a cancelled or completed game and, for completed games,
+    /// record the settlement on their profile. Anyone can call it, once per player and game.
+    /// Cancel and payout leave profiles to this so they need no account per player.
+    pub fn release_active_game(
+        ctx: Context<ReleaseActiveGame>,
+        game_id: u64,
+        player_pubkey: Pubkey,
+    ) -> Result<()> {
+        let game = &mut ctx.accounts.game;
 
-    /// Update contract configuration (admin only)
-    pub fn update_config(
-        ctx: Context<UpdateConfig>,
+        require!(game.id == game_id, GameError::InvalidGameId);
+        require!(
+            matches!(game.status, GameStatus::Completed | GameStatus::Cancelled),
+            GameError::GameNotSettled
+        );
+        let index = game.players
+            .iter()
+            .position(|p| p.player == player_pubkey)
+            .ok_or(GameError::PlayerNotInGame)?;
+        require!(game.profiles_released & (1 << index) == 0, GameError::ProfileAlreadyReleased);
+        game.profiles_released |= 1 << index;
+
+        let staked = game.players[index].amount;
+        let completed = game.status == GameStatus::Completed;
+        let prize = game.prizes.iter().find(|prize| prize.player == player_pubkey);
+        let slot = Clock::get()?.slot;
+        update_profile(&ctx.accounts.player_profile, &player_pubkey, |profile| {
+            if completed {
+                profile.record_settlement(
+                    staked,
+                    prize.map(|prize| prize.place),
+                    prize.map_or(0, |prize| prize.amount),
+                    slot,
+                );
+            }
+            profile.close_game();
+        })?;
+
+        emit!(ActiveGameReleased {
+            game_id,
+            player: player_pubkey,
+        });
+
+        Ok(())
+    }
+
+    /// Lower the fee immediately (config authority only); every other change is timelocked
+    pub fn update_config(ctx: Context<UpdateConfig>, fee_bps: u16) -> Result<()> {
+        let config = &mut ctx.accounts.game_config;
+
+        // Validate config authority
+        require!(ctx.accounts.admin.key() == config.admin, GameError::UnauthorizedAccess);
+        require!(fee_bps < config.fee_bps, GameError::FeeIncreaseRequiresTimelock);
+
+        let old = config.snapshot();
+        config.fee_bps = fee_bps;
+
+        emit!(ConfigUpdated {
+            updated_by: ctx.accounts.admin.key(),
+            old,
+            new: config.snapshot(),
+        });
+
+        ctx.accounts.admin_action_log.record(
+            ctx.accounts.admin.key(),
+            AdminAction::UpdateConfig,
+            0,
+            fee_bps as u64,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Queue a config change that can be executed once the timelock has elapsed (config authority only)
+    #[allow(clippy::too_many_arguments)]
+    pub fn queue_config_change(
+        ctx: Context<QueueConfigChange>,
         fee_bps: Option<u16>,
-        first_place_percentage: Option<u8>,
-        second_place_percentage: Option<u8>,
-        third_place_percentage: Option<u8>,
+        prize_percentages: Option<[u8; 3]>,
         fee_receiver: Option<Pubkey>,
+        config_timelock_secs: Option<i64>,
+        max_gas_fee_lamports: Option<u64>,
+        gas_reimbursement: Option<Pubkey>,
+        max_active_games_per_player: Option<u16>,
+        referral_share_bps: Option<u16>,
+        fee_tiers: Option<[FeeTier; MAX_FEE_TIERS]>,
+        balance_withdrawal_cooldown_secs: Option<i64>,
+    ) -> Result<()> {
+        let config = &ctx.accounts.game_config;
+        let pending = &mut ctx.accounts.pending_change;
+
+        // Validate config authority
+        require!(ctx.accounts.admin.key() == config.admin, GameError::UnauthorizedAccess);
+        require!(
+            fee_bps.is_some()
+                || prize_percentages.is_some()
+                || fee_receiver.is_some()
+                || config_timelock_secs.is_some()
+                || max_gas_fee_lamports.is_some()
+                || gas_reimbursement.is_some()
+                || max_active_games_per_player.is_some()
+                || referral_share_bps.is_some()
+                || fee_tiers.is_some()
+                || balance_withdrawal_cooldown_secs.is_some(),
+            GameError::EmptyConfigChange
+        );
+
+        pending.proposer = ctx.accounts.admin.key();
+        pending.fee_bps = fee_bps;
+        pending.prize_percentages = prize_percentages;
+        pending.fee_receiver = fee_receiver;
+        pending.config_timelock_secs = config_timelock_secs;
+        pending.max_gas_fee_lamports = max_gas_fee_lamports;
+        pending.gas_reimbursement = gas_reimbursement;
+        pending.max_active_games_per_player = max_active_games_per_player;
+        pending.referral_share_bps = referral_share_bps;
+        pending.fee_tiers = fee_tiers;
+        pending.balance_withdrawal_cooldown_secs = balance_withdrawal_cooldown_secs;
+        pending.validate()?;
+
+        let now = Clock::get()?.unix_timestamp;
+        pending.queued_at = now;
+        pending.executable_at = now + config.config_timelock_secs;
+        pending.bump = ctx.bumps.pending_change;
+
+        emit!(ConfigChangeQueued {
+            fee_bps,
+            prize_percentages,
+            fee_receiver,
+            config_timelock_secs,
+            max_gas_fee_lamports,
+            gas_reimbursement,
+            max_active_games_per_player,
+            referral_share_bps,
+            fee_tiers,
+            balance_withdrawal_cooldown_secs,
+            executable_at: pending.executable_at,
+        });
+
+        ctx.accounts.admin_action_log.record(
+            ctx.accounts.admin.key(),
+            AdminAction::QueueConfigChange,
+            0,
+            0,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Apply the queued config change after its timelock (config authority only)
+    pub fn execute_config_change(ctx: Context<ExecuteConfigChange>) -> Result<()> {
+        let config = &mut ctx.accounts.game_config;
+        let pending = &ctx.accounts.pending_change;
+
+        // Validate config authority
+        require!(ctx.accounts.admin.key() == config.admin, GameError::UnauthorizedAccess);
+        require!(
+            Clock::get()?.unix_timestamp >= pending.executable_at,
+            GameError::TimelockNotElapsed
+        );
+
+        let old = config.snapshot();
+
+        if let Some(fee) = pending.fee_bps {
+            config.fee_bps = fee;
+        }
+
+        if let Some([first, second, third]) = pending.prize_percentages {
+            config.first_place_percentage = first;
+            config.second_place_percentage = second;
+            config.third_place_percentage = third;
+        }
+
+        if let Some(receiver) = pending.fee_receiver {
+            config.fee_receiver = receiver;
+        }
+
+        if let Some(timelock) = pending.config_timelock_secs {
+            config.config_timelock_secs = timelock;
+        }
+
+        if let Some(max_gas_fee) = pending.max_gas_fee_lamports {
+            config.max_gas_fee_lamports = max_gas_fee;
+        }
+
+        if let Some(reimbursement) = pending.gas_reimbursement {
+            config.gas_reimbursement = reimbursement;
+        }
+
+        if let Some(limit) = pending.max_active_games_per_player {
+            config.max_active_games_per_player = limit;
+        }
+
+        if let Some(share) = pending.referral_share_bps {
+            config.referral_share_bps = share;
+        }
+
+        if let Some(tiers) = pending.fee_tiers {
+            config.fee_tiers = tiers;
+        }
+
+        if let Some(cooldown) = pending.balance_withdrawal_cooldown_secs {
+            config.balance_withdrawal_cooldown_secs = cooldown;
+        }
+
+        emit!(ConfigChangeExecuted {
+            fee_bps: pending.fee_bps,
+            prize_percentages: pending.prize_percentages,
+            fee_receiver: pending.fee_receiver,
+            config_timelock_secs: pending.config_timelock_secs,
+            max_gas_fee_lamports: pending.max_gas_fee_lamports,
+            gas_reimbursement: pending.gas_reimbursement,
+            max_active_games_per_player: pending.max_active_games_per_player,
+            referral_share_bps: pending.referral_share_bps,
+            fee_tiers: pending.fee_tiers,
+            balance_withdrawal_cooldown_secs: pending.balance_withdrawal_cooldown_secs,
+        });
+
+        emit!(ConfigUpdated {
+            updated_by: ctx.accounts.admin.key(),
+            old,
+            new: config.snapshot(),
+        });
+
+        ctx.accounts.admin_action_log.record(
+            ctx.accounts.admin.key(),
+            AdminAction::ExecuteConfigChange,
+            0,
+            0,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Drop the queued config change (config authority only)
+    pub fn cancel_config_change(ctx: Context<CancelConfigChange>) -> Result<()> {
+        let config = &ctx.accounts.game_config;
+
+        // Validate config authority
+        require!(ctx.accounts.admin.key() == config.admin, GameError::UnauthorizedAccess);
+
+        emit!(ConfigChangeCancelled {
+            queued_at: ctx.accounts.pending_change.queued_at,
+        });
+
+        ctx.accounts.admin_action_log.record(
+            ctx.accounts.admin.key(),
+            AdminAction::CancelConfigChange,
+            0,
+            0,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Set up the M-of-N approval set (config authority, once). Later changes need a proposal.
+    pub fn create_multisig(ctx: Context<CreateMultisig>, signers: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        let config = &ctx.accounts.game_config;
+        let multisig = &mut ctx.accounts.multisig;
+
+        // Validate config authority
+        require!(ctx.accounts.admin.key() == config.admin, GameError::UnauthorizedAccess);
+        validate_signer_set(&signers, threshold)?;
+
+        // Every other approver co-signs the setup, so the config authority cannot name keys alone
+        for signer in signers.iter().filter(|s| **s != ctx.accounts.admin.key()) {
+            require!(
+                ctx.remaining_accounts.iter().any(|acc| acc.key() == *signer && acc.is_signer),
+                GameError::MissingSignerConsent
+            );
+        }
+
+        multisig.signers = signers.clone();
+        multisig.threshold = threshold;
+        multisig.version = 0;
+        multisig.proposal_count = 0;
+        multisig.bump = ctx.bumps.multisig;
+
+        emit!(MultisigUpdated { signers, threshold, version: 0 });
+
+        ctx.accounts.admin_action_log.record(
+            ctx.accounts.admin.key(),
+            AdminAction::CreateMultisig,
+            0,
+            threshold as u64,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Open a proposal for a dangerous admin action; the proposer's approval is counted
+    pub fn create_proposal(
+        ctx: Context<CreateProposal>,
+        action: ProposalAction,
+        expires_in_secs: i64,
     ) -> Result<()> {
+        let multisig = &mut ctx.accounts.multisig;
+        let proposal = &mut ctx.accounts.proposal;
+        let proposer = ctx.accounts.proposer.key();
+
+        let signer_index = multisig.signer_index(&proposer)?;
+        require!(
+            (1..=MAX_PROPOSAL_LIFETIME_SECS).contains(&expires_in_secs),
+            GameError::InvalidProposalLifetime
+        );
+        if let ProposalAction::SetSigners { signers, threshold } = &action {
+            validate_signer_set(signers, *threshold)?;
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        proposal.id = multisig.proposal_count;
+        proposal.proposer = proposer;
+        proposal.action = action.clone();
+        proposal.approvals = 1 << signer_index;
+        proposal.signer_set_version = multisig.version;
+        proposal.created_at = now;
+        proposal.expires_at = now + expires_in_secs;
+        proposal.executed = false;
+        proposal.bump = ctx.bumps.proposal;
+
+        multisig.proposal_count += 1;
+
+        emit!(ProposalCreated {
+            proposal_id: proposal.id,
+            proposer,
+            action,
+            expires_at: proposal.expires_at,
+        });
+
+        ctx.accounts.admin_action_log.record(
+            proposer,
+            AdminAction::CreateProposal,
+            0,
+            proposal.id,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Add the signer's approval to a live proposal
+    pub fn approve_proposal(ctx: Context<ApproveProposal>, proposal_id: u64) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        let proposal = &mut ctx.accounts.proposal;
+        let signer = ctx.accounts.signer.key();
+
+        let signer_index = multisig.signer_index(&signer)?;
+        proposal.check_live(multisig)?;
+        require!(proposal.approvals & (1 << signer_index) == 0, GameError::AlreadyApproved);
+
+        proposal.approvals |= 1 << signer_index;
+
+        emit!(ProposalApproved {
+            proposal_id,
+            signer,
+            approvals: proposal.approvals.count_ones() as u8,
+        });
+
+        ctx.accounts.admin_action_log.record(
+            signer,
+            AdminAction::ApproveProposal,
+            0,
+            proposal_id,
+            0,
+        )?;
+
+        Ok(())
+    }
+
+    /// Carry out a proposal that reached the threshold before expiring (any approver)
+    pub fn execute_proposal(ctx: Context<ExecuteProposal>, proposal_id: u64) -> Result<()> {
+        let multisig = &mut ctx.accounts.multisig;
+        let proposal = &mut ctx.accounts.proposal;
         let config = &mut ctx.accounts.game_config;
 
-        // Validate admin
-        require!(ctx.accounts.admin.key() == config.admin, GameError::UnauthorizedAccess);
+        multisig.signer_index(&ctx.accounts.executor.key())?;
+        proposal.check_live(multisig)?;
+        require!(
+            proposal.approvals.count_ones() >= multisig.threshold as u32,
+            GameError::ThresholdNotMet
+        );
+
+        proposal.executed = true;
+        let mut amount_moved = 0;
+
+        match proposal.action.clone() {
+            ProposalAction::WithdrawLiabilities { recipient, amount } => {
+                require!(!config.is_paused(PAUSE_WITHDRAWALS), GameError::Paused);
+                require!(ctx.accounts.vault.lamports() >= amount, GameError::InsufficientVaultBalance);
+
+                let surplus = vault_surplus(&ctx.accounts.vault, config)?;
+
+                let recipient_account = ctx.remaining_accounts
+                    .iter()
+                    .find(|acc| acc.key() == recipient)
+                    .ok_or(GameError::MissingRecipientAccount)?;
+
+                // Transfer funds
+                **ctx.accounts.vault.to_account_info().try_borrow_mut_lamports()? -= amount;
+                **recipient_account.try_borrow_mut_lamports()? += amount;
+                amount_moved = amount;
+
+                emit!(LiabilitiesWithdrawn {
+                    proposal_id,
+                    recipient,
+                    amount,
+                    outstanding_liabilities: config.outstanding_liabilities,
+                    uncovered_liabilities: amount.saturating_sub(surplus),
+                });
+            }
+            ProposalAction::SetAuthority { role, new_authority } => {
+                let previous = config.role_authority(&role);
+                config.set_role_authority(&role, new_authority);
+
+                emit!(RoleUpdated {
+                    role,
+                    previous,
+                    new_authority,
+                });
+            }
+            ProposalAction::OverrideTimelock { queued_at } => {
+                let pending = ctx.accounts.pending_change
+                    .as_mut()
+                    .ok_or(GameError::PendingChangeMismatch)?;
+                require!(pending.queued_at == queued_at, GameError::PendingChangeMismatch);
+
+                // The config authority can now execute the queued change right away
+                pending.executable_at = Clock::get()?.unix_timestamp;
+            }
+            ProposalAction::SetSigners { signers, threshold } => {
+                validate_signer_set(&signers, threshold)?;
+                multisig.signers = signers.clone();
+                multisig.threshold = threshold;
+                // Invalidates approvals collected under the old set
+                multisig.version += 1;
+
+                emit!(MultisigUpdated {
+                    signers,
+                    threshold,
+                    version: multisig.version,
+                });
+            }
+        }
+
+        emit!(ProposalExecuted {
+            proposal_id,
+            executor: ctx.accounts.executor.key(),
+            approvals: proposal.approvals.count_ones() as u8,
+        });
+
+        ctx.accounts.admin_action_log.record(
+            ctx.accounts.executor.key(),
+            AdminAction::ExecuteProposal,
+            0,
+            amount_moved,
+            0,
+        )?;
+
+        Ok(())
+    }
+}
+
+// Helper function removed - no longer needed since we don't track player states
+
+/// Load a player profile passed as a remaining account, apply `update` and write it back.
+/// Players who joined before profiles existed have none; their empty profile PDA is skipped.
+fn update_profile(
+    info: &AccountInfo,
+    player: &Pubkey,
+    update: impl FnOnce(&mut PlayerProfile),
+) -> Result<()> {
+    if info.data_is_empty() {
+        let (expected, _) =
+            Pubkey::find_program_address(&[b"player_profile", player.as_ref()], &crate::ID);
+        require!(info.key() == expected, GameError::InvalidProfileAccount);
+        return Ok(());
+    }
+    require!(
+        info.owner == &crate::ID && info.is_writable,
+        GameError::InvalidProfileAccount
+    );
+    let mut profile = PlayerProfile::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+    // Profiles are only ever created at their wallet's PDA, so the stored wallet identifies it
+    require!(profile.player == *player, GameError::InvalidProfileAccount);
+
+    update(&mut profile);
+    profile.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+/// Bind `referrer` to a profile that has none yet, then credit the bound referrer with
+/// `share_bps` of `fee`. Returns the credited amount, which the caller must move to the vault.
+fn apply_referral(
+    profile: &mut PlayerProfile,
+    referrer: Option<Pubkey>,
+    referrer_profile: Option<&mut PlayerProfile>,
+    share_bps: u16,
+    fee: u64,
+) -> Result<u64> {
+    if profile.referrer == Pubkey::default() {
+        if let Some(referrer) = referrer {
+            require!(referrer != profile.player, GameError::InvalidReferrer);
+            profile.referrer = referrer;
+
+            emit!(ReferrerBound {
+                player: profile.player,
+                referrer,
+            });
+        }
+    }
+
+    if profile.referrer == Pubkey::default() {
+        return Ok(0);
+    }
+
+    let referrer_profile = referrer_profile.ok_or(GameError::MissingReferrerProfile)?;
+    require!(referrer_profile.player == profile.referrer, GameError::InvalidReferrer);
+
+    let share = (fee as u128 * share_bps as u128 / 10000) as u64;
+    referrer_profile.referral_rewards += share;
+    Ok(share)
+}
+
+/// Lamports in a player's internal balance, i.e. everything above the account's rent
+fn balance_available(balance: &Account<PlayerBalance>) -> Result<u64> {
+    let rent = Rent::get()?.minimum_balance(PlayerBalance::SIZE);
+    Ok(balance.to_account_info().lamports().saturating_sub(rent))
+}
+
+/// The player taking a seat and the accounts their entry is charged to
+struct Entrant<'a, 'info> {
+    player: Pubkey,
+    profile: &'a mut PlayerProfile,
+    profile_bump: u8,
+    referrer: Option<Pubkey>,
+    referrer_profile: Option<&'a mut PlayerProfile>,
+    payer: &'a Signer<'info>,
+    player_balance: Option<&'a Account<'info, PlayerBalance>>,
+    vault: &'a SystemAccount<'info>,
+    fee_receiver: &'a UncheckedAccount<'info>,
+    system_program: &'a Program<'info, System>,
+}
+
+/// Set up a freshly created game account with `creator` staked in the first seat and emit
+/// `GameCreated`. Shared by `create_game` and `create_rematch`.
+fn open_game(
+    config: &mut GameConfig,
+    game: &mut Game,
+    game_bump: u8,
+    game_type: GameType,
+    tier: GameTier,
+    creator: Entrant,
+) -> Result<()> {
+    let creator_key = creator.player;
+    let stake_amount = tier.to_lamports();
+
+    // Initialize game
+    game.id = config.next_game_id;
+    game.creator = creator_key;
+    game.game_type = game_type.clone();
+    game.tier = tier.clone();
+    game.stake_per_player = stake_amount;
+    game.max_players = game_type.max_players();
+    game.status = GameStatus::WaitingForPlayers;
+    game.players = Vec::new();
+    game.stake_terms = Vec::new();
+    game.total_pot = 0;
+    game.gas_fees_deducted = 0;
+    game.created_at = Clock::get()?.unix_timestamp;
+    game.bump = game_bump;
+
+    let terms = take_seat(config, game, creator)?;
+
+    config.next_game_id += 1;
+
+    emit!(GameCreated {
+        game_id: game.id,
+        creator: creator_key,
+        payer: terms.payer,
+        game_type,
+        tier,
+        stake_amount,
+        fee_bps: terms.fee_bps,
+    });
+
+    Ok(())
+}
+
+/// Seat `entrant` at the game's stake: pick their fee tier, charge the entry, count the game
+/// toward their active limit and share the fee with their referrer. Shared by `open_game` and
+/// `join_game`; returns the terms recorded for the seat.
+fn take_seat(config: &mut GameConfig, game: &mut Game, entrant: Entrant) -> Result<StakeTerms> {
+    let stake_amount = game.stake_per_player;
+    let fee_bps = config.fee_bps_for(entrant.profile.lamports_staked);
+    let fee_amount = (stake_amount as u128 * fee_bps as u128 / 10000) as u64;
+    let terms = StakeTerms {
+        fee_bps,
+        payer: entry_funder(entrant.payer, entrant.player_balance, stake_amount + fee_amount)?,
+        from_balance: entrant.player_balance.is_some(),
+    };
+
+    game.add_player(
+        PlayerStake {
+            player: entrant.player,
+            amount: stake_amount,
+            joined_at: Clock::get()?.unix_timestamp,
+        },
+        terms.clone(),
+    );
+    game.total_pot += stake_amount;
+    config.outstanding_liabilities += stake_amount;
+
+    entrant.profile.touch(entrant.player, entrant.profile_bump)?;
+    entrant.profile.open_game(config.max_active_games_per_player)?;
+
+    let referral_amount = apply_referral(
+        entrant.profile,
+        entrant.referrer,
+        entrant.referrer_profile,
+        config.referral_share_bps,
+        fee_amount,
+    )?;
+    if referral_amount > 0 {
+        // The referrer's share stays in the vault until claimed
+        config.outstanding_liabilities += referral_amount;
+        emit!(ReferralFeeShared {
+            game_id: game.id,
+            player: entrant.player,
+            referrer: entrant.profile.referrer,
+            amount: referral_amount,
+        });
+    }
+
+    // Transfer stake and fee
+    collect_entry(
+        entrant.payer,
+        entrant.player_balance,
+        entrant.vault,
+        entrant.fee_receiver,
+        entrant.system_program,
+        stake_amount + referral_amount,
+        fee_amount - referral_amount,
+    )?;
+
+    Ok(terms)
+}
+
+/// The account an entry of `total_cost` is paid from: the player's balance when passed, else
+/// the payer. Fails unless it holds enough.
+fn entry_funder(
+    payer: &Signer,
+    player_balance: Option<&Account<PlayerBalance>>,
+    total_cost: u64,
+) -> Result<Pubkey> {
+    let (funder, available) = match player_balance {
+        Some(balance) => (balance.key(), balance_available(balance)?),
+        None => (payer.key(), payer.lamports()),
+    };
+    require!(available >= total_cost, GameError::InsufficientFunds);
+    Ok(funder)
+}
+
+/// Move an entry's stake share to the vault and the rest of the fee to the fee receiver,
+/// from the player's internal balance when one is passed and from the payer otherwise
+fn collect_entry<'info>(
+    payer: &Signer<'info>,
+    player_balance: Option<&Account<'info, PlayerBalance>>,
+    vault: &SystemAccount<'info>,
+    fee_receiver: &UncheckedAccount<'info>,
+    system_program: &Program<'info, System>,
+    to_vault: u64,
+    to_fee_receiver: u64,
+) -> Result<()> {
+    if let Some(balance) = player_balance {
+        **balance.to_account_info().try_borrow_mut_lamports()? -= to_vault + to_fee_receiver;
+        **vault.to_account_info().try_borrow_mut_lamports()? += to_vault;
+        **fee_receiver.to_account_info().try_borrow_mut_lamports()? += to_fee_receiver;
+        return Ok(());
+    }
+
+    transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            Transfer {
+                from: payer.to_account_info(),
+                to: vault.to_account_info(),
+            },
+        ),
+        to_vault,
+    )?;
+
+    transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            Transfer {
+                from: payer.to_account_info(),
+                to: fee_receiver.to_account_info(),
+            },
+        ),
+        to_fee_receiver,
+    )
+}
+
+/// Send gas deducted from refunds or prizes to the reimbursement account and record it on the game
+fn reimburse_gas(
+    game: &mut Game,
+    vault: &SystemAccount,
+    gas_reimbursement: &UncheckedAccount,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    **vault.to_account_info().try_borrow_mut_lamports()? -= amount;
+    **gas_reimbursement.try_borrow_mut_lamports()? += amount;
+    game.gas_fees_deducted += amount;
+    Ok(())
+}
+
+/// Active tiers come first, with rising volume thresholds and falling fees
+fn validate_fee_tiers(tiers: &[FeeTier]) -> Result<()> {
+    let active = tiers.iter().take_while(|tier| tier.is_active()).count();
+    require!(
+        tiers[active..].iter().all(|tier| *tier == FeeTier::default()),
+        GameError::InvalidFeeTiers
+    );
+    for (i, tier) in tiers[..active].iter().enumerate() {
+        require!(tier.fee_bps <= 10000, GameError::InvalidFeeTiers);
+        if let Some(previous) = i.checked_sub(1).map(|j| &tiers[j]) {
+            require!(
+                tier.min_volume_lamports > previous.min_volume_lamports
+                    && tier.fee_bps <= previous.fee_bps,
+                GameError::InvalidFeeTiers
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Signer sets are up to MAX_MULTISIG_SIGNERS unique keys with a threshold of at least 2 that
+/// the set can reach, so no single key can approve alone
+fn validate_signer_set(signers: &[Pubkey], threshold: u8) -> Result<()> {
+    require!(
+        !signers.is_empty() && signers.len() <= MAX_MULTISIG_SIGNERS,
+        GameError::InvalidSignerSet
+    );
+    require!(
+        threshold >= 2 && threshold as usize <= signers.len(),
+        GameError::InvalidSignerSet
+    );
+    for (i, signer) in signers.iter().enumerate() {
+        require!(!signers[..i].contains(signer), GameError::InvalidSignerSet);
+    }
+    Ok(())
+}
+
+/// Fill fields missing from an older config layout. `data` must already be `GameConfig::SIZE`
+/// bytes, zero-extended past the old layout. Returns the version migrated from.
+fn migrate_config_data(data: &mut [u8], vault_owed: u64) -> Result<u8> {
+    let from_version = data[GameConfig::VERSION_OFFSET];
+    require!(from_version < CONFIG_VERSION, GameError::ConfigAlreadyCurrent);
+
+    let mut config = GameConfig::try_deserialize(&mut &data[..])?;
+
+    if from_version < 1 {
+        // Version 0 is the original layout, which ended at `bump`
+        config.settlement_authority = config.admin;
+        config.pauser = config.admin;
+        config.treasury_authority = config.admin;
+        config.paused = 0;
+        config.config_timelock_secs = DEFAULT_CONFIG_TIMELOCK_SECS;
+        config.outstanding_liabilities = vault_owed;
+        config.max_gas_fee_lamports = DEFAULT_MAX_GAS_FEE_LAMPORTS;
+        config.gas_reimbursement = config.fee_receiver;
+    }
+
+    if from_version < 2 {
+        // Version 1 ended at `gas_reimbursement`
+        config.max_active_games_per_player = DEFAULT_MAX_ACTIVE_GAMES_PER_PLAYER;
+    }
+
+    if from_version < 3 {
+        // Version 2 ended at `max_active_games_per_player`; referrals start switched off
+        config.referral_share_bps = 0;
+    }
+
+    if from_version < 4 {
+        // Version 3 ended at `referral_share_bps`; everyone pays the base fee until tiers are set
+        config.fee_tiers = [FeeTier::default(); MAX_FEE_TIERS];
+    }
+
+    if from_version < 5 {
+        // Version 4 ended at `fee_tiers`
+        config.blocklist_authority = config.admin;
+    }
+
+    if from_version < 6 {
+        // Version 5 ended at `blocklist_authority`; balances can be withdrawn right away
+        config.balance_withdrawal_cooldown_secs = 0;
+    }
+
+    if from_version < 7 {
+        // Version 6 ended at `balance_withdrawal_cooldown_secs`
+        config.reserved = [0; CONFIG_RESERVED_BYTES];
+    }
+
+    config.version = CONFIG_VERSION;
+    config.try_serialize(&mut &mut data[..])?;
+
+    Ok(from_version)
+}
+
+/// Vault lamports not owed to any unsettled game and not needed for rent
+fn vault_surplus(vault: &SystemAccount, config: &GameConfig) -> Result<u64> {
+    let rent = Rent::get()?.minimum_balance(0);
+    Ok(vault
+        .lamports()
+        .saturating_sub(rent)
+        .saturating_sub(config.outstanding_liabilities))
+}
+
+/// Move a game to Active, seeding its bracket or standings from the latest slot hash
+fn start_game(
+    game: &mut Game,
+    bracket: Option<&mut Bracket>,
+    standings: Option<&mut Standings>,
+    slot_hashes: &AccountInfo,
+) -> Result<()> {
+    game.status = GameStatus::Active;
+
+    if game.game_type.uses_bracket() || game.game_type.uses_standings() {
+        // Seed from the latest slot hash mixed with join data, so anyone can recompute it
+        let (seed_slot, recent_hash) = latest_slot_hash(slot_hashes)?;
+        let seed = bracket_seed(&recent_hash, game);
+        let players = shuffle_players(&seed, game.players.iter().map(|p| p.player).collect());
+
+        match game.game_type {
+            GameType::Tournament { .. } => {
+                let bracket = bracket.ok_or(GameError::InvalidBracketAccount)?;
+                bracket.seed = seed;
+                bracket.seed_slot = seed_slot;
+                bracket.build_single_elimination(players)?;
+            }
+            GameType::DoubleElimination { bracket_reset, .. } => {
+                let bracket = bracket.ok_or(GameError::InvalidBracketAccount)?;
+                bracket.seed = seed;
+                bracket.seed_slot = seed_slot;
+                bracket.build_double_elimination(players, bracket_reset)?;
+            }
+            GameType::Swiss { rounds, .. } => {
+                let standings = standings.ok_or(GameError::InvalidStandingsAccount)?;
+                standings.seed = seed;
+                standings.seed_slot = seed_slot;
+                standings.start_swiss(players, rounds)?;
+
+                emit!(SwissRoundPaired {
+                    game_id: game.id,
+                    round: standings.round,
+                    pairings: standings.pairings.clone(),
+                });
+            }
+            GameType::League { .. } => {
+                let standings = standings.ok_or(GameError::InvalidStandingsAccount)?;
+                standings.seed = seed;
+                standings.seed_slot = seed_slot;
+                standings.start_league(players)?;
+            }
+            GameType::OneVsOne => {}
+        }
+
+        emit!(BracketSeeded {
+            game_id: game.id,
+            seed,
+            seed_slot,
+        });
+    }
+
+    emit!(GameStarted {
+        game_id: game.id,
+        players: game.players.iter().map(|p| p.player).collect(),
+    });
+
+    Ok(())
+}
+
+/// Winners must name exactly the recorded placements, each at its own place
+fn check_placements(placements: &[Option<Pubkey>; PAID_PLACES], winners: &[WinnerPayout]) -> Result<()> {
+    let placed = placements.iter().filter(|p| p.is_some()).count();
+    require!(winners.len() == placed, GameError::PlacementMismatch);
+
+    // Bit i set once place i + 1 is paid, so no place is paid twice
+    let mut seen = 0u8;
+    for winner in winners.iter() {
+        let index = (winner.place as usize).wrapping_sub(1);
+        let expected = placements.get(index).copied().flatten();
+        require!(expected == Some(winner.player), GameError::PlacementMismatch);
+        require!(seen & (1 << index) == 0, GameError::PlacementMismatch);
+        seen |= 1 << index;
+    }
+
+    Ok(())
+}
+
+// Account structs
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = GameConfig::SIZE,
+        seeds = [b"game_config"],
+        bump
+    )]
+    pub game_config: Account<'info, GameConfig>,
+    
+    #[account(
+        init,
+        payer = admin,
+        space = AdminActionLog::SIZE,
+        seeds = [b"admin_action_log"],
+        bump
+    )]
+    pub admin_action_log: Account<'info, AdminActionLog>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    /// CHECK: Fee receiver can be any account
+    pub fee_receiver: UncheckedAccount<'info>,
+    
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ GameError::UnauthorizedAccess)]
+    pub program: Program<'info, crate::program::GamingVault>,
+    
+    /// Only the upgrade authority of the deployed program may initialize the config
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ GameError::UnauthorizedAccess)]
+    pub program_data: Account<'info, ProgramData>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    /// CHECK: May still have an older layout; discriminator and admin are checked by hand
+    #[account(mut, owner = crate::ID, seeds = [b"game_config"], bump)]
+    pub game_config: UncheckedAccount<'info>,
+    
+    /// Created here for configs from before the log existed
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = AdminActionLog::SIZE,
+        seeds = [b"admin_action_log"],
+        bump
+    )]
+    pub admin_action_log: Account<'info, AdminActionLog>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    #[account(seeds = [b"vault"], bump)]
+    pub vault: SystemAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(game_type: GameType)]
+pub struct CreateGame<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = Game::space(game_type.max_players() as usize),
+        seeds = [b"game", game_config.next_game_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub game: Account<'info, Game>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = Bracket::space(&game_type),
+        seeds = [b"bracket", game_config.next_game_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub bracket: Option<Account<'info, Bracket>>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = Standings::space(&game_type),
+        seeds = [b"standings", game_config.next_game_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub standings: Option<Account<'info, Standings>>,
+    
+    #[account(mut, seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    /// Takes the seat and receives any winnings
+    pub creator: Signer<'info>,
+    
+    /// Funds the entry; may equal the player.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = PlayerProfile::SIZE,
+        seeds = [b"player_profile", creator.key().as_ref()],
+        bump
+    )]
+    pub creator_profile: Account<'info, PlayerProfile>,
+    
+    #[account(seeds = [b"blocked", creator.key().as_ref()], bump)]
+    /// CHECK: The creator's blocklist entry; must not exist
+    pub blocked: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"player_profile", referrer_profile.player.as_ref()],
+        bump = referrer_profile.bump
+    )]
+    pub referrer_profile: Option<Account<'info, PlayerProfile>>,
+    
+    #[account(
+        mut,
+        seeds = [b"player_balance", creator.key().as_ref()],
+        bump = player_balance.bump
+    )]
+    /// Pays the stake and fee instead of `payer` when passed
+    pub player_balance: Option<Account<'info, PlayerBalance>>,
+    
+    #[account(mut, seeds = [b"vault"], bump)]
+    pub vault: SystemAccount<'info>,
+    
+    #[account(mut, address = game_config.fee_receiver @ GameError::FeeReceiverMismatch)]
+    /// CHECK: Must be the configured fee receiver
+    pub fee_receiver: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(game_id: u64)]
+pub struct JoinGame<'info> {
+    /// Grown to the current size so games created before `stake_terms` have room for them;
+    /// never shrunk, which would hand the creator's rent to the joining payer
+    #[account(
+        mut,
+        seeds = [b"game", game_id.to_le_bytes().as_ref()],
+        bump,
+        realloc = Game::space(game.max_players as usize).max(game.to_account_info().data_len()),
+        realloc::payer = payer,
+        realloc::zero = false
+    )]
+    pub game: Account<'info, Game>,
+    
+    #[account(mut, seeds = [b"bracket", game_id.to_le_bytes().as_ref()], bump)]
+    pub bracket: Option<Account<'info, Bracket>>,
+    
+    #[account(mut, seeds = [b"standings", game_id.to_le_bytes().as_ref()], bump)]
+    pub standings: Option<Account<'info, Standings>>,
+    
+    #[account(mut, seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    /// Takes the seat and receives any winnings
+    pub player: Signer<'info>,
+    
+    /// Funds the entry; may equal the player.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = PlayerProfile::SIZE,
+        seeds = [b"player_profile", player.key().as_ref()],
+        bump
+    )]
+    pub player_profile: Account<'info, PlayerProfile>,
+    
+    #[account(seeds = [b"blocked", player.key().as_ref()], bump)]
+    /// CHECK: The player's blocklist entry; must not exist
+    pub blocked: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"player_profile", referrer_profile.player.as_ref()],
+        bump = referrer_profile.bump
+    )]
+    pub referrer_profile: Option<Account<'info, PlayerProfile>>,
+    
+    #[account(
+        mut,
+        seeds = [b"player_balance", player.key().as_ref()],
+        bump = player_balance.bump
+    )]
+    /// Pays the stake and fee instead of `payer` when passed
+    pub player_balance: Option<Account<'info, PlayerBalance>>,
+    
+    #[account(mut, seeds = [b"vault"], bump)]
+    pub vault: SystemAccount<'info>,
+    
+    #[account(mut, address = game_config.fee_receiver @ GameError::FeeReceiverMismatch)]
+    /// CHECK: Must be the configured fee receiver
+    pub fee_receiver: UncheckedAccount<'info>,
+    
+    #[account(address = slot_hashes::ID)]
+    /// CHECK: SlotHashes sysvar, read raw because it is too large to deserialize on-chain
+    pub slot_hashes: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(prev_game_id: u64)]
+pub struct CreateRematch<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = Game::space(2),
+        seeds = [b"game", game_config.next_game_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub game: Account<'info, Game>,
+    
+    #[account(
+        mut,
+        seeds = [b"game", prev_game_id.to_le_bytes().as_ref()],
+        bump = previous_game.bump
+    )]
+    pub previous_game: Account<'info, Game>,
+    
+    #[account(mut, seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    /// A player of the previous game; takes the first seat
+    pub creator: Signer<'info>,
+    
+    /// Funds the entry; may equal the player.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = PlayerProfile::SIZE,
+        seeds = [b"player_profile", creator.key().as_ref()],
+        bump
+    )]
+    pub creator_profile: Account<'info, PlayerProfile>,
+    
+    #[account(seeds = [b"blocked", creator.key().as_ref()], bump)]
+    /// CHECK: The creator's blocklist entry; must not exist
+    pub blocked: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"player_profile", referrer_profile.player.as_ref()],
+        bump = referrer_profile.bump
+    )]
+    pub referrer_profile: Option<Account<'info, PlayerProfile>>,
+    
+    #[account(
+        mut,
+        seeds = [b"player_balance", creator.key().as_ref()],
+        bump = player_balance.bump
+    )]
+    /// Pays the stake and fee instead of `payer` when passed
+    pub player_balance: Option<Account<'info, PlayerBalance>>,
+    
+    #[account(mut, seeds = [b"vault"], bump)]
+    pub vault: SystemAccount<'info>,
+    
+    #[account(mut, address = game_config.fee_receiver @ GameError::FeeReceiverMismatch)]
+    /// CHECK: Must be the configured fee receiver
+    pub fee_receiver: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimReferralRewards<'info> {
+    #[account(mut, seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"player_profile", referrer.key().as_ref()],
+        bump = referrer_profile.bump
+    )]
+    pub referrer_profile: Account<'info, PlayerProfile>,
+    
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+    
+    #[account(mut, seeds = [b"vault"], bump)]
+    pub vault: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterSessionKey<'info> {
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = SessionKey::SIZE,
+        seeds = [b"session_key", player.key().as_ref()],
+        bump
+    )]
+    pub session: Account<'info, SessionKey>,
+    
+    /// The wallet itself; a session key cannot manage sessions
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeSessionKey<'info> {
+    #[account(
+        mut,
+        close = player,
+        seeds = [b"session_key", player.key().as_ref()],
+        bump = session.bump
+    )]
+    pub session: Account<'info, SessionKey>,
+    
+    #[account(mut)]
+    pub player: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DepositBalance<'info> {
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = PlayerBalance::SIZE,
+        seeds = [b"player_balance", player.key().as_ref()],
+        bump
+    )]
+    pub player_balance: Account<'info, PlayerBalance>,
+    
+    #[account(seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    #[account(seeds = [b"blocked", player.key().as_ref()], bump)]
+    /// CHECK: The player's blocklist entry; must not exist
+    pub blocked: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawBalance<'info> {
+    #[account(
+        mut,
+        seeds = [b"player_balance", player.key().as_ref()],
+        bump = player_balance.bump
+    )]
+    pub player_balance: Account<'info, PlayerBalance>,
+    
+    #[account(seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    #[account(mut)]
+    pub player: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(game_id: u64)]
+pub struct AdminStartGame<'info> {
+    #[account(
+        mut,
+        seeds = [b"game", game_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub game: Account<'info, Game>,
+    
+    #[account(mut, seeds = [b"bracket", game_id.to_le_bytes().as_ref()], bump = bracket.bump)]
+    pub bracket: Option<Account<'info, Bracket>>,
+    
+    #[account(mut, seeds = [b"standings", game_id.to_le_bytes().as_ref()], bump = standings.bump)]
+    pub standings: Option<Account<'info, Standings>>,
+    
+    #[account(seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    #[account(mut, seeds = [b"admin_action_log"], bump = admin_action_log.bump)]
+    pub admin_action_log: Account<'info, AdminActionLog>,
+    
+    pub admin: Signer<'info>,
+    
+    #[account(address = slot_hashes::ID)]
+    /// CHECK: SlotHashes sysvar, read raw because it is too large to deserialize on-chain
+    pub slot_hashes: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(game_id: u64, player_pubkey: Pubkey)]
+pub struct AdminWithdrawPlayer<'info> {
+    #[account(
+        mut,
+        seeds = [b"game", game_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub game: Account<'info, Game>,
+    
+    #[account(mut, seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    #[account(mut, seeds = [b"admin_action_log"], bump = admin_action_log.bump)]
+    pub admin_action_log: Account<'info, AdminActionLog>,
+    
+    pub admin: Signer<'info>,
+    
+    #[account(mut, seeds = [b"player_profile", player_pubkey.as_ref()], bump)]
+    /// CHECK: Loaded by `update_profile`, which skips it while it was never created
+    pub player_profile: UncheckedAccount<'info>,
+    
+    #[account(mut, seeds = [b"vault"], bump)]
+    pub vault: SystemAccount<'info>,
+    
+    #[account(mut, address = game_config.gas_reimbursement)]
+    /// CHECK: Receives the gas deducted from refunds and prizes
+    pub gas_reimbursement: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(game_id: u64)]
+pub struct AdminCancelGame<'info> {
+    #[account(
+        mut,
+        seeds = [b"game", game_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub game: Account<'info, Game>,
+    
+    #[account(
+        mut,
+        seeds = [b"bracket", game_id.to_le_bytes().as_ref()],
+        bump = bracket.bump,
+        has_one = rent_payer @ GameError::RentPayerMismatch,
+        close = rent_payer
+    )]
+    pub bracket: Option<Account<'info, Bracket>>,
+    
+    #[account(
+        mut,
+        seeds = [b"standings", game_id.to_le_bytes().as_ref()],
+        bump = standings.bump,
+        has_one = rent_payer @ GameError::RentPayerMismatch,
+        close = rent_payer
+    )]
+    pub standings: Option<Account<'info, Standings>>,
+    
+    #[account(mut, seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    #[account(mut, seeds = [b"admin_action_log"], bump = admin_action_log.bump)]
+    pub admin_action_log: Account<'info, AdminActionLog>,
+    
+    pub admin: Signer<'info>,
+    
+    #[account(mut, seeds = [b"vault"], bump)]
+    pub vault: SystemAccount<'info>,
+    
+    #[account(mut, address = game_config.gas_reimbursement)]
+    /// CHECK: Receives the gas deducted from refunds and prizes
+    pub gas_reimbursement: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    /// CHECK: Gets the bracket or standings rent back; must match its `rent_payer`
+    pub rent_payer: Option<UncheckedAccount<'info>>,
+    
+    /// The game a rematch was opened from; required when cancelling a rematch
+    #[account(
+        mut,
+        seeds = [b"game", game.previous_game_id.to_le_bytes().as_ref()],
+        bump = previous_game.bump
+    )]
+    pub previous_game: Option<Account<'info, Game>>,
+}
+
+#[derive(Accounts)]
+#[instruction(game_id: u64)]
+pub struct AdminPayoutWinners<'info> {
+    #[account(
+        mut,
+        seeds = [b"game", game_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub game: Account<'info, Game>,
+    
+    #[account(
+        mut,
+        seeds = [b"bracket", game_id.to_le_bytes().as_ref()],
+        bump = bracket.bump,
+        has_one = rent_payer @ GameError::RentPayerMismatch,
+        close = rent_payer
+    )]
+    pub bracket: Option<Account<'info, Bracket>>,
+    
+    #[account(
+        mut,
+        seeds = [b"standings", game_id.to_le_bytes().as_ref()],
+        bump = standings.bump,
+        has_one = rent_payer @ GameError::RentPayerMismatch,
+        close = rent_payer
+    )]
+    pub standings: Option<Account<'info, Standings>>,
+    
+    #[account(mut, seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    #[account(mut, seeds = [b"admin_action_log"], bump = admin_action_log.bump)]
+    pub admin_action_log: Account<'info, AdminActionLog>,
+    
+    pub admin: Signer<'info>,
+    
+    #[account(mut, seeds = [b"vault"], bump)]
+    pub vault: SystemAccount<'info>,
+    
+    #[account(mut, address = game_config.gas_reimbursement)]
+    /// CHECK: Receives the gas deducted from refunds and prizes
+    pub gas_reimbursement: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    /// CHECK: Gets the bracket or standings rent back; must match its `rent_payer`
+    pub rent_payer: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(game_id: u64)]
+pub struct AdvanceWinner<'info> {
+    #[account(seeds = [b"game", game_id.to_le_bytes().as_ref()], bump)]
+    pub game: Account<'info, Game>,
+    
+    #[account(mut, seeds = [b"bracket", game_id.to_le_bytes().as_ref()], bump = bracket.bump)]
+    pub bracket: Account<'info, Bracket>,
+    
+    #[account(seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    #[account(mut, seeds = [b"admin_action_log"], bump = admin_action_log.bump)]
+    pub admin_action_log: Account<'info, AdminActionLog>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(game_id: u64)]
+pub struct RecordSwissResult<'info> {
+    #[account(seeds = [b"game", game_id.to_le_bytes().as_ref()], bump)]
+    pub game: Account<'info, Game>,
+    
+    #[account(mut, seeds = [b"standings", game_id.to_le_bytes().as_ref()], bump = standings.bump)]
+    pub standings: Account<'info, Standings>,
+    
+    #[account(seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    #[account(mut, seeds = [b"admin_action_log"], bump = admin_action_log.bump)]
+    pub admin_action_log: Account<'info, AdminActionLog>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(game_id: u64, a: u8, b: u8)]
+pub struct RecordLeagueResult<'info> {
+    #[account(seeds = [b"game", game_id.to_le_bytes().as_ref()], bump)]
+    pub game: Account<'info, Game>,
+    
+    #[account(mut, seeds = [b"standings", game_id.to_le_bytes().as_ref()], bump = standings.bump)]
+    pub standings: Account<'info, Standings>,
+    
+    #[account(
+        init,
+        payer = admin,
+        space = LeagueMatch::SIZE,
+        seeds = [b"league_match", game_id.to_le_bytes().as_ref(), &[a], &[b]],
+        bump
+    )]
+    pub league_match: Account<'info, LeagueMatch>,
+    
+    #[account(seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    #[account(mut, seeds = [b"admin_action_log"], bump = admin_action_log.bump)]
+    pub admin_action_log: Account<'info, AdminActionLog>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AdminEmergencyWithdraw<'info> {
+    #[account(seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    #[account(mut, seeds = [b"admin_action_log"], bump = admin_action_log.bump)]
+    pub admin_action_log: Account<'info, AdminActionLog>,
+    
+    pub admin: Signer<'info>,
+    
+    #[account(mut, seeds = [b"vault"], bump)]
+    pub vault: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMultisig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = Multisig::SIZE,
+        seeds = [b"multisig"],
+        bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+    
+    #[account(seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    #[account(mut, seeds = [b"admin_action_log"], bump = admin_action_log.bump)]
+    pub admin_action_log: Account<'info, AdminActionLog>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(
+        init,
+        payer = proposer,
+        space = Proposal::SIZE,
+        seeds = [b"proposal", multisig.proposal_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    
+    #[account(mut, seeds = [b"multisig"], bump = multisig.bump)]
+    pub multisig: Account<'info, Multisig>,
+    
+    #[account(mut, seeds = [b"admin_action_log"], bump = admin_action_log.bump)]
+    pub admin_action_log: Account<'info, AdminActionLog>,
+    
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct ApproveProposal<'info> {
+    #[account(mut, seeds = [b"proposal", proposal_id.to_le_bytes().as_ref()], bump = proposal.bump)]
+    pub proposal: Account<'info, Proposal>,
+    
+    #[account(seeds = [b"multisig"], bump = multisig.bump)]
+    pub multisig: Account<'info, Multisig>,
+    
+    #[account(mut, seeds = [b"admin_action_log"], bump = admin_action_log.bump)]
+    pub admin_action_log: Account<'info, AdminActionLog>,
+    
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct ExecuteProposal<'info> {
+    #[account(mut, seeds = [b"proposal", proposal_id.to_le_bytes().as_ref()], bump = proposal.bump)]
+    pub proposal: Account<'info, Proposal>,
+    
+    #[account(mut, seeds = [b"multisig"], bump = multisig.bump)]
+    pub multisig: Account<'info, Multisig>,
+    
+    #[account(mut, seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    #[account(mut, seeds = [b"admin_action_log"], bump = admin_action_log.bump)]
+    pub admin_action_log: Account<'info, AdminActionLog>,
+    
+    #[account(mut, seeds = [b"vault"], bump)]
+    pub vault: SystemAccount<'info>,
+    
+    /// Only needed for `OverrideTimelock`
+    #[account(mut, seeds = [b"pending_config_change"], bump)]
+    pub pending_change: Option<Account<'info, PendingConfigChange>>,
+    
+    pub executor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    #[account(mut, seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    #[account(mut, seeds = [b"admin_action_log"], bump = admin_action_log.bump)]
+    pub admin_action_log: Account<'info, AdminActionLog>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct BlockWallet<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = Blocked::SIZE,
+        seeds = [b"blocked", wallet.as_ref()],
+        bump
+    )]
+    pub blocked: Account<'info, Blocked>,
+    
+    #[account(seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    #[account(mut, seeds = [b"admin_action_log"], bump = admin_action_log.bump)]
+    pub admin_action_log: Account<'info, AdminActionLog>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct UnblockWallet<'info> {
+    #[account(
+        mut,
+        close = blocked_by,
+        seeds = [b"blocked", wallet.as_ref()],
+        bump = blocked.bump
+    )]
+    pub blocked: Account<'info, Blocked>,
+    
+    #[account(seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    #[account(mut, seeds = [b"admin_action_log"], bump = admin_action_log.bump)]
+    pub admin_action_log: Account<'info, AdminActionLog>,
+    
+    pub admin: Signer<'info>,
+    
+    #[account(mut, address = blocked.blocked_by)]
+    /// CHECK: Receives the blocklist entry rent back
+    pub blocked_by: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(player_pubkey: Pubkey)]
+pub struct ResyncActiveGames<'info> {
+    #[account(
+        mut,
+        seeds = [b"player_profile", player_pubkey.as_ref()],
+        bump = player_profile.bump
+    )]
+    pub player_profile: Account<'info, PlayerProfile>,
+    
+    #[account(seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    #[account(mut, seeds = [b"admin_action_log"], bump = admin_action_log.bump)]
+    pub admin_action_log: Account<'info, AdminActionLog>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(game_id: u64, player_pubkey: Pubkey)]
+pub struct ReleaseActiveGame<'info> {
+    #[account(
+        mut,
+        seeds = [b"game", game_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub game: Account<'info, Game>,
+    
+    #[account(mut, seeds = [b"player_profile", player_pubkey.as_ref()], bump)]
+    /// CHECK: Loaded by `update_profile`, which skips it while it was never created
+    pub player_profile: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(role: AdminRole)]
+pub struct ProposeAuthority<'info> {
+    #[account(
+        init,
+        payer = proposer,
+        space = AuthorityProposal::SIZE,
+        seeds = [b"authority_proposal", role.seed().to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, AuthorityProposal>,
+    
+    #[account(seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    #[account(mut, seeds = [b"admin_action_log"], bump = admin_action_log.bump)]
+    pub admin_action_log: Account<'info, AdminActionLog>,
+    
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(role: AdminRole)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"authority_proposal", role.seed().to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, AuthorityProposal>,
+    
+    #[account(mut, seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    #[account(mut, seeds = [b"admin_action_log"], bump = admin_action_log.bump)]
+    pub admin_action_log: Account<'info, AdminActionLog>,
+    
+    pub new_authority: Signer<'info>,
+    
+    #[account(mut, address = proposal.proposer)]
+    /// CHECK: Receives the proposal rent back
+    pub proposer: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(role: AdminRole)]
+pub struct CancelAuthorityProposal<'info> {
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"authority_proposal", role.seed().to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, AuthorityProposal>,
+    
+    #[account(seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    #[account(mut, seeds = [b"admin_action_log"], bump = admin_action_log.bump)]
+    pub admin_action_log: Account<'info, AdminActionLog>,
+    
+    pub signer: Signer<'info>,
+    
+    #[account(mut, address = proposal.proposer)]
+    /// CHECK: Receives the proposal rent back
+    pub proposer: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(mut, seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    #[account(mut, seeds = [b"admin_action_log"], bump = admin_action_log.bump)]
+    pub admin_action_log: Account<'info, AdminActionLog>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct QueueConfigChange<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = PendingConfigChange::SIZE,
+        seeds = [b"pending_config_change"],
+        bump
+    )]
+    pub pending_change: Account<'info, PendingConfigChange>,
+    
+    #[account(seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    #[account(mut, seeds = [b"admin_action_log"], bump = admin_action_log.bump)]
+    pub admin_action_log: Account<'info, AdminActionLog>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteConfigChange<'info> {
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"pending_config_change"],
+        bump = pending_change.bump
+    )]
+    pub pending_change: Account<'info, PendingConfigChange>,
+    
+    #[account(mut, seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    #[account(mut, seeds = [b"admin_action_log"], bump = admin_action_log.bump)]
+    pub admin_action_log: Account<'info, AdminActionLog>,
+    
+    pub admin: Signer<'info>,
+    
+    #[account(mut, address = pending_change.proposer)]
+    /// CHECK: Receives the pending change rent back
+    pub proposer: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelConfigChange<'info> {
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"pending_config_change"],
+        bump = pending_change.bump
+    )]
+    pub pending_change: Account<'info, PendingConfigChange>,
+    
+    #[account(seeds = [b"game_config"], bump)]
+    pub game_config: Account<'info, GameConfig>,
+    
+    #[account(mut, seeds = [b"admin_action_log"], bump = admin_action_log.bump)]
+    pub admin_action_log: Account<'info, AdminActionLog>,
+    
+    pub admin: Signer<'info>,
+    
+    #[account(mut, address = pending_change.proposer)]
+    /// CHECK: Receives the pending change rent back
+    pub proposer: UncheckedAccount<'info>,
+}
+
+// Data structures
+/// Lifetime stats for one wallet, kept by the program so leaderboards can trust them
+#[account]
+pub struct PlayerProfile {
+    pub player: Pubkey,
+    /// Settled games; refunded or cancelled games do not count
+    pub games_played: u32,
+    pub wins: u32,
+    /// Paid finishes (1st, 2nd or 3rd)
+    pub podium_finishes: u32,
+    /// Stakes in settled games
+    pub lamports_staked: u64,
+    /// Prizes received, after gas deductions
+    pub lamports_won: u64,
+    pub last_active_slot: u64,
+    pub bump: u8,
+    /// Games joined or created that have not been settled, cancelled or left yet
+    pub active_games: u16,
+    /// Wallet that referred this player, bound on first use; default while unset
+    pub referrer: Pubkey,
+    /// Referral fee shares earned and not yet claimed
+    pub referral_rewards: u64,
+}
+
+impl PlayerProfile {
+    /// Includes 86 bytes of room for later fields
+    pub const SIZE: usize = 8 + 32 + 4 + 4 + 4 + 8 + 8 + 8 + 1 + 2 + 32 + 8 + 86;
+
+    /// Set up a fresh profile on first use and mark the wallet active
+    pub fn touch(&mut self, player: Pubkey, bump: u8) -> Result<()> {
+        if self.player == Pubkey::default() {
+            self.player = player;
+            self.bump = bump;
+        }
+        self.last_active_slot = Clock::get()?.slot;
+        Ok(())
+    }
+
+    /// Count a newly entered game against the per-player limit
+    pub fn open_game(&mut self, limit: u16) -> Result<()> {
+        require!(self.active_games < limit, GameError::TooManyActiveGames);
+        self.active_games += 1;
+        Ok(())
+    }
+
+    /// Release a game slot; saturating because profiles predating the counter start at 0
+    pub fn close_game(&mut self) {
+        self.active_games = self.active_games.saturating_sub(1);
+    }
+
+    pub fn record_settlement(&mut self, staked: u64, place: Option<u8>, won: u64, slot: u64) {
+        self.games_played += 1;
+        self.lamports_staked += staked;
+        if place == Some(1) {
+            self.wins += 1;
+        }
+        if place.is_some() {
+            self.podium_finishes += 1;
+        }
+        self.lamports_won += won;
+        self.last_active_slot = slot;
+    }
+}
+
+/// A player's internal balance: the account's lamports above rent. Entries can be paid from it,
+/// and refunds and winnings of those entries are credited back to it.
+#[account]
+pub struct PlayerBalance {
+    pub player: Pubkey,
+    /// Withdrawals wait `balance_withdrawal_cooldown_secs` from here
+    pub last_deposit_at: i64,
+    pub bump: u8,
+}
+
+impl PlayerBalance {
+    pub const SIZE: usize = 8 + 32 + 8 + 1;
+
+    /// Withdrawals open once `cooldown_secs` have passed since the last deposit
+    pub fn check_cooldown(&self, cooldown_secs: i64, now: i64) -> Result<()> {
+        require!(now >= self.last_deposit_at + cooldown_secs, GameError::BalanceCooldownActive);
+        Ok(())
+    }
+}
+
+/// A short-lived key that may make gameplay moves for `player` in a few games
+#[account]
+pub struct SessionKey {
+    pub player: Pubkey,
+    pub session_key: Pubkey,
+    /// Games the key is limited to; 0 marks an unused slot
+    pub game_ids: [u64; MAX_SESSION_GAMES],
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+impl SessionKey {
+    pub const SIZE: usize = 8 + 32 + 32 + 8 * MAX_SESSION_GAMES + 8 + 1;
+
+    /// Whether `signer` may make a gameplay move for `player` in `game_id`: the wallet itself,
+    /// or its session key while live and scoped to that game. Gameplay instructions take the
+    /// `["session_key", player]` account as optional and call this; anything that moves funds
+    /// requires the wallet instead.
+    pub fn check_session(
+        session: Option<&SessionKey>,
+        player: &Pubkey,
+        signer: &Pubkey,
+        game_id: u64,
+        now: i64,
+    ) -> Result<()> {
+        if signer == player {
+            return Ok(());
+        }
+        let session = session.ok_or(GameError::SessionNotAuthorized)?;
+        require!(
+            session.player == *player
+                && session.session_key == *signer
+                && now < session.expires_at
+                && game_id != 0
+                && session.game_ids.contains(&game_id),
+            GameError::SessionNotAuthorized
+        );
+        Ok(())
+    }
+}
+
+#[account]
+pub struct GameConfig {
+    /// Config authority: fees, prize table, role assignments
+    pub admin: Pubkey,
+    pub fee_receiver: Pubkey,
+    pub fee_bps: u16,
+    pub first_place_percentage: u8,
+    pub second_place_percentage: u8,
+    pub third_place_percentage: u8,
+    pub next_game_id: u64,
+    pub bump: u8,
+    /// Layout version, see `CONFIG_VERSION`; accounts from before versioning read as 0
+    pub version: u8,
+    /// Starts, settles, refunds and cancels games; the always-online backend key
+    pub settlement_authority: Pubkey,
+    /// Sets the circuit-breaker flags
+    pub pauser: Pubkey,
+    /// Emergency withdrawals from the vault
+    pub treasury_authority: Pubkey,
+    /// Bitmask of `PAUSE_*` flags
+    pub paused: u8,
+    /// Delay between queueing and executing a config change
+    pub config_timelock_secs: i64,
+    /// Sum of `total_pot` across games that are neither completed nor cancelled, plus unclaimed
+    /// referral rewards
+    pub outstanding_liabilities: u64,
+    /// Upper bound for any per-player or per-winner gas deduction
+    pub max_gas_fee_lamports: u64,
+    /// Receives gas deducted from refunds and prizes
+    pub gas_reimbursement: Pubkey,
+    /// Unsettled games a wallet may be in at once, counted on its profile
+    pub max_active_games_per_player: u16,
+    /// Share of a referred player's fee credited to the referrer, in basis points of the fee
+    pub referral_share_bps: u16,
+    /// Volume discounts, see `fee_bps_for`
+    pub fee_tiers: [FeeTier; MAX_FEE_TIERS],
+    /// Blocks and unblocks wallets
+    pub blocklist_authority: Pubkey,
+    /// Wait after a deposit before the balance can be withdrawn; 0 disables it
+    pub balance_withdrawal_cooldown_secs: i64,
+    /// Zeroed space for future fields, so adding one needs no reallocation
+    pub reserved: [u8; CONFIG_RESERVED_BYTES],
+}
+
+impl GameConfig {
+    pub const SIZE: usize = 8 + 32 + 32 + 2 + 1 + 1 + 1 + 8 + 1 + 1 + 32 + 32 + 32 + 1 + 8 + 8 + 8 + 32 + 2 + 2 + MAX_FEE_TIERS * FeeTier::SIZE + 32 + 8 + CONFIG_RESERVED_BYTES;
+
+    /// Size of the original, unversioned layout (up to and including `bump`)
+    pub const LEGACY_SIZE: usize = 8 + 32 + 32 + 2 + 1 + 1 + 1 + 8 + 1;
+
+    /// The version byte directly follows the legacy layout
+    pub const VERSION_OFFSET: usize = Self::LEGACY_SIZE;
+
+    pub fn snapshot(&self) -> ConfigSnapshot {
+        ConfigSnapshot {
+            fee_bps: self.fee_bps,
+            prize_percentages: [
+                self.first_place_percentage,
+                self.second_place_percentage,
+                self.third_place_percentage,
+            ],
+            fee_receiver: self.fee_receiver,
+            config_timelock_secs: self.config_timelock_secs,
+            max_gas_fee_lamports: self.max_gas_fee_lamports,
+            gas_reimbursement: self.gas_reimbursement,
+            max_active_games_per_player: self.max_active_games_per_player,
+            referral_share_bps: self.referral_share_bps,
+            fee_tiers: self.fee_tiers,
+            balance_withdrawal_cooldown_secs: self.balance_withdrawal_cooldown_secs,
+        }
+    }
+
+    /// Fee for a player with `volume` lamports of settled stakes: the highest tier reached,
+    /// but never more than the base fee
+    pub fn fee_bps_for(&self, volume: u64) -> u16 {
+        self.fee_tiers
+            .iter()
+            .filter(|tier| tier.is_active() && volume >= tier.min_volume_lamports)
+            .map(|tier| tier.fee_bps)
+            .next_back()
+            .map_or(self.fee_bps, |fee_bps| fee_bps.min(self.fee_bps))
+    }
+
+    pub fn release_liability(&mut self, amount: u64) {
+        self.outstanding_liabilities = self.outstanding_liabilities.saturating_sub(amount);
+    }
+
+    pub fn is_paused(&self, flag: u8) -> bool {
+        self.paused & flag != 0
+    }
+
+    pub fn role_authority(&self, role: &AdminRole) -> Pubkey {
+        match role {
+            AdminRole::Config => self.admin,
+            AdminRole::Settlement => self.settlement_authority,
+            AdminRole::Pauser => self.pauser,
+            AdminRole::Treasury => self.treasury_authority,
+            AdminRole::Blocklist => self.blocklist_authority,
+        }
+    }
+
+    pub fn set_role_authority(&mut self, role: &AdminRole, authority: Pubkey) {
+        match role {
+            AdminRole::Config => self.admin = authority,
+            AdminRole::Settlement => self.settlement_authority = authority,
+            AdminRole::Pauser => self.pauser = authority,
+            AdminRole::Treasury => self.treasury_authority = authority,
+            AdminRole::Blocklist => self.blocklist_authority = authority,
+        }
+    }
+
+    /// Prize percentage for `place` when only `places_paid` places were filled.
+    /// Shares of places nobody reached (e.g. a 2-player bracket) go to first place.
+    pub fn place_percentage(&self, place: u8, places_paid: usize) -> Result<u8> {
+        let table = [
+            self.first_place_percentage,
+            self.second_place_percentage,
+            self.third_place_percentage,
+        ];
+        require!(
+            place >= 1 && (place as usize) <= places_paid.min(table.len()),
+            GameError::InvalidPlace
+        );
+
+        let mut percentage = table[place as usize - 1];
+        if place == 1 {
+            percentage += table.iter().skip(places_paid).sum::<u8>();
+        }
+        Ok(percentage)
+    }
+}
+
+#[account]
+pub struct Game {
+    pub id: u64,
+    pub creator: Pubkey,
+    pub game_type: GameType,
+    pub tier: GameTier,
+    pub stake_per_player: u64,
+    pub max_players: u8,
+    pub status: GameStatus,
+    pub players: Vec<PlayerStake>,
+    pub total_pot: u64,
+    pub created_at: i64,
+    pub bump: u8,
+    /// Gas deducted from this game's refunds and prizes
+    pub gas_fees_deducted: u64,
+    /// Only this wallet may take the open seat (rematches); default when open to anyone
+    pub reserved_for: Pubkey,
+    /// Game this one is a rematch of, 0 if none
+    pub previous_game_id: u64,
+    /// Rematch opened from this game, 0 if none
+    pub rematch_game_id: u64,
+    /// Entry terms in `players` order. Kept apart from `PlayerStake` so games created before
+    /// they were recorded still deserialize; those read as empty (see `Game::terms`).
+    pub stake_terms: Vec<StakeTerms>,
+    /// What each winner was paid, for `release_active_game`
+    pub prizes: Vec<Prize>,
+    /// Players, by index in `players`, whose profile has been released
+    pub profiles_released: u32,
+}
+
+// impl Game {
+//     pub const SIZE: usize = 8 + 8 + 32 + (1 + 1) + 1 + 8 + 1 + 1 + (4 + 16 * (32 + 8 + 8)) + 8 + 8 + 1;
+// }
+
+
+impl Game {
+    /// Where `player`'s winnings go: their balance if they entered from it, else the wallet
+    pub fn payout_account(&self, player: &Pubkey) -> Pubkey {
+        self.players
+            .iter()
+            .position(|p| p.player == *player)
+            .map(|i| self.terms(i))
+            .filter(|terms| terms.from_balance)
+            .map_or(*player, |terms| terms.payer)
+    }
+
+    /// Whether `player` may take an open seat. A rematch seat is held for the previous opponent
+    /// for `REMATCH_RESERVATION_SECS`, then opens to anyone.
+    pub fn seat_open_to(&self, player: &Pubkey, now: i64) -> bool {
+        self.reserved_for == Pubkey::default()
+            || self.reserved_for == *player
+            || now >= self.created_at + REMATCH_RESERVATION_SECS
+    }
+
+    /// Entry terms of the player at `index`; players who joined before terms were recorded
+    /// paid for themselves at an unrecorded fee
+    pub fn terms(&self, index: usize) -> StakeTerms {
+        self.stake_terms.get(index).cloned().unwrap_or(StakeTerms {
+            fee_bps: 0,
+            payer: self.players[index].player,
+            from_balance: false,
+        })
+    }
+
+    /// Seat a player, first filling in terms for anyone who joined before they were recorded
+    /// so `stake_terms` stays parallel to `players`
+    pub fn add_player(&mut self, stake: PlayerStake, terms: StakeTerms) {
+        while self.stake_terms.len() < self.players.len() {
+            let legacy = self.terms(self.stake_terms.len());
+            self.stake_terms.push(legacy);
+        }
+        self.players.push(stake);
+        self.stake_terms.push(terms);
+    }
+
+    pub fn remove_player(&mut self, index: usize) -> (PlayerStake, StakeTerms) {
+        let terms = self.terms(index);
+        if index < self.stake_terms.len() {
+            self.stake_terms.remove(index);
+        }
+        (self.players.remove(index), terms)
+    }
+}
+
+impl Game {
+    /// Account size for a game with `max_players` seats
+    pub fn space(max_players: usize) -> usize {
+        8 + 8 + 32 + 3 + 1 + 8 + 1 + 1 + (4 + max_players * (32 + 8 + 8)) + 8 + 8 + 1 + 8 + 32 + 8 + 8
+            + (4 + max_players * StakeTerms::SIZE) + (4 + PAID_PLACES * Prize::SIZE) + 4 + 500
+    }
+}
+
+/// Number of privileged actions kept in the admin action log
+pub const ADMIN_LOG_CAPACITY: usize = 32;
+
+/// Current `GameConfig` layout version
+pub const CONFIG_VERSION: u8 = 7;
+
+/// Bytes kept free at the end of `GameConfig` for fields added later
+pub const CONFIG_RESERVED_BYTES: usize = 128;
+
+/// Config timelock given to migrated configs (48 hours)
+pub const DEFAULT_CONFIG_TIMELOCK_SECS: i64 = 48 * 60 * 60;
+
+/// Default cap on a single gas deduction (0.001 SOL)
+pub const DEFAULT_MAX_GAS_FEE_LAMPORTS: u64 = 1_000_000;
+
+/// Unsettled games per wallet for new and migrated configs
+pub const DEFAULT_MAX_ACTIVE_GAMES_PER_PLAYER: u16 = 5;
+
+/// How long a rematch seat is held for the previous opponent (1 hour)
+pub const REMATCH_RESERVATION_SECS: i64 = 60 * 60;
+
+/// Games a single session key can be scoped to
+pub const MAX_SESSION_GAMES: usize = 4;
+
+/// Longest a session key can stay valid (24 hours)
+pub const MAX_SESSION_LIFETIME_SECS: i64 = 24 * 60 * 60;
+
+/// Volume discount slots in `GameConfig`
+pub const MAX_FEE_TIERS: usize = 4;
+
+/// Largest M-of-N approval set
+pub const MAX_MULTISIG_SIGNERS: usize = 10;
+
+/// Longest a proposal stays open for approvals and execution (7 days)
+pub const MAX_PROPOSAL_LIFETIME_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// Longest delay a config change can be held for (30 days)
+pub const MAX_CONFIG_TIMELOCK_SECS: i64 = 30 * 24 * 60 * 60;
+
+/// Longest wait between a deposit and withdrawing the balance (7 days)
+pub const MAX_BALANCE_WITHDRAWAL_COOLDOWN_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// Pause flags stored in `GameConfig::paused`
+pub const PAUSE_CREATE: u8 = 1 << 0;
+pub const PAUSE_JOIN: u8 = 1 << 1;
+pub const PAUSE_SETTLEMENT: u8 = 1 << 2;
+pub const PAUSE_WITHDRAWALS: u8 = 1 << 3;
+pub const PAUSE_DEPOSITS: u8 = 1 << 4;
+pub const PAUSE_ALL: u8 =
+    PAUSE_CREATE | PAUSE_JOIN | PAUSE_SETTLEMENT | PAUSE_WITHDRAWALS | PAUSE_DEPOSITS;
+
+/// Largest field any game can hold (Swiss)
+pub const MAX_GAME_PLAYERS: usize = MAX_SWISS_SIZE;
+
+// PlayerState struct removed - backend handles all player eligibility
+
+/// Largest field a single tournament bracket can hold
+pub const MAX_TOURNAMENT_SIZE: usize = 16;
+
+/// Number of paid places in a tournament
+pub const PAID_PLACES: usize = 3;
+
+/// Marks a bracket route that leads nowhere
+pub const NO_MATCH: u8 = u8::MAX;
+
+/// Largest Swiss field; opponents are tracked in a u64 bitmask
+pub const MAX_SWISS_SIZE: usize = 32;
+
+/// Largest league; every pairing is its own match account
+pub const MAX_LEAGUE_SIZE: usize = 16;
+
+/// Marks the empty side of a Swiss bye
+pub const NO_PLAYER: u8 = u8::MAX;
+
+/// Cap on search steps when pairing a Swiss round
+pub const MAX_PAIRING_STEPS: u32 = 512;
+
+/// Most Swiss rounds for a field: ceil(log2(size)) + 2, and never more than half the field
+/// so a rematch-free pairing always exists and is found quickly
+pub fn max_swiss_rounds(size: usize) -> u8 {
+    let log2 = usize::BITS - size.saturating_sub(1).leading_zeros();
+    (log2 as usize + 2).min(size / 2) as u8
+}
+
+#[account]
+pub struct Bracket {
+    pub game_id: u64,
+    pub seed: [u8; 32],
+    pub seed_slot: u64,
+    pub size: u8,
+    pub players: Vec<Pubkey>,
+    pub matches: Vec<BracketMatch>,
+    pub placements: [Option<Pubkey>; PAID_PLACES],
+    pub byes: Vec<Pubkey>,
+    pub grand_final: u8,
+    pub reset_match: u8,
+    pub completed: bool,
+    pub bump: u8,
+    /// Paid the account's rent and gets it back when the game is settled
+    pub rent_payer: Pubkey,
+}
+
+impl Bracket {
+    /// Account size for the bracket of `game_type`: a single-elimination field of `n` has at
+    /// most `n` matches with the third-place match, a double-elimination one `2n - 1` with the reset
+    pub fn space(game_type: &GameType) -> usize {
+        let size = game_type.max_players() as usize;
+        let matches = match game_type {
+            GameType::DoubleElimination { .. } => 2 * size - 1,
+            _ => size,
+        };
+        8 + 8 + 32 + 8 + 1
+            + (4 + size * 32)
+            + (4 + matches * BracketMatch::SIZE)
+            + PAID_PLACES * (1 + 32)
+            + (4 + size * 32)
+            + 1 + 1 + 1 + 1
+            + 32
+    }
+
+    /// Lay out a single-elimination bracket (plus third-place match) for players in seed order.
+    /// Fields that are not a power of two are padded with byes for the top seeds.
+    pub fn build_single_elimination(&mut self, players: Vec<Pubkey>) -> Result<()> {
+        require!(
+            (2..=MAX_TOURNAMENT_SIZE).contains(&players.len()),
+            GameError::InvalidTournamentSize
+        );
+
+        let size = players.len().next_power_of_two();
+        let rounds = size.trailing_zeros() as u8;
+        let positions = seed_positions(size);
+        let third_place = if size >= 4 { (size - 1) as u8 } else { NO_MATCH };
+
+        let mut matches = Vec::with_capacity(size);
+        let mut offset = 0;
+        for round in 0..rounds {
+            let count = size >> (round + 1);
+            for p in 0..count {
+                let mut m = BracketMatch::new(round);
+
+                if round + 1 < rounds {
+                    m.winner_to = (offset + count + p / 2) as u8;
+                    m.winner_slot = (p % 2) as u8;
+                } else {
+                    m.winner_place = 1;
+                    m.loser_place = 2;
+                }
+
+                // Semifinal losers drop into the third-place match
+                if round + 2 == rounds {
+                    m.loser_to = third_place;
+                    m.loser_slot = (p % 2) as u8;
+                }
+
+                // Seed positions past the field are byes
+                if round == 0 {
+                    m.players = [
+                        players.get(positions[2 * p]).copied(),
+                        players.get(positions[2 * p + 1]).copied(),
+                    ];
+                    m.pending = 0;
+                }
+
+                matches.push(m);
+            }
+            offset += count;
+        }
+
+        if third_place != NO_MATCH {
+            let mut m = BracketMatch::new(rounds - 1);
+            m.side = BracketSide::ThirdPlace;
+            m.winner_place = 3;
+            matches.push(m);
+        }
+
+        self.size = size as u8;
+        self.players = players;
+        self.matches = matches;
+        self.placements = [None; PAID_PLACES];
+        self.byes = Vec::new();
+        self.grand_final = NO_MATCH;
+        self.reset_match = NO_MATCH;
+        self.completed = false;
+
+        for index in 0..(size / 2) {
+            self.resolve(index)?;
+        }
+
+        Ok(())
+    }
+
+    /// Lay out a double-elimination bracket: winners bracket, losers bracket and grand final,
+    /// plus a reset match when `bracket_reset` is set. Requires a full power-of-two field.
+    pub fn build_double_elimination(&mut self, players: Vec<Pubkey>, bracket_reset: bool) -> Result<()> {
+        let size = players.len();
+        require!(
+            size.is_power_of_two() && (4..=MAX_TOURNAMENT_SIZE).contains(&size),
+            GameError::InvalidTournamentSize
+        );
+
+        let wb_rounds = size.trailing_zeros() as usize;
+        let lb_rounds = 2 * (wb_rounds - 1);
+        let wb_offset = |round: usize| size - (size >> round);
+        let wb_count = |round: usize| size >> (round + 1);
+        // Losers rounds alternate: even rounds pair survivors, odd rounds take winners-bracket drop-ins
+        let lb_count = |round: usize| size >> (round / 2 + 2);
+        let lb_offset: Vec<usize> = (0..lb_rounds)
+            .scan(size - 1, |offset, round| {
+                let start = *offset;
+                *offset += lb_count(round);
+                Some(start)
+            })
+            .collect();
+        let grand_final = lb_offset[lb_rounds - 1] + 1;
+        let positions = seed_positions(size);
+
+        let mut matches = Vec::with_capacity(grand_final + 2);
+        for round in 0..wb_rounds {
+            let count = wb_count(round);
+            for p in 0..count {
+                let mut m = BracketMatch::new(round as u8);
+                m.side = BracketSide::Winners;
+
+                if round + 1 < wb_rounds {
+                    m.winner_to = (wb_offset(round + 1) + p / 2) as u8;
+                    m.winner_slot = (p % 2) as u8;
+                } else {
+                    m.winner_to = grand_final as u8;
+                    m.winner_slot = 0;
+                }
+
+                // First-round losers pair up; later losers cross over to limit rematches
+                if round == 0 {
+                    m.loser_to = (lb_offset[0] + p / 2) as u8;
+                    m.loser_slot = (p % 2) as u8;
+                    m.players = [
+                        Some(players[positions[2 * p]]),
+                        Some(players[positions[2 * p + 1]]),
+                    ];
+                    m.pending = 0;
+                } else {
+                    m.loser_to = (lb_offset[2 * round - 1] + count - 1 - p) as u8;
+                    m.loser_slot = 1;
+                }
+
+                matches.push(m);
+            }
+        }
+
+        for round in 0..lb_rounds {
+            for p in 0..lb_count(round) {
+                let mut m = BracketMatch::new(round as u8);
+                m.side = BracketSide::Losers;
+
+                if round + 1 == lb_rounds {
+                    m.winner_to = grand_final as u8;
+                    m.winner_slot = 1;
+                    m.loser_place = 3;
+                } else if round % 2 == 0 {
+                    m.winner_to = (lb_offset[round + 1] + p) as u8;
+                    m.winner_slot = 0;
+                } else {
+                    m.winner_to = (lb_offset[round + 1] + p / 2) as u8;
+                    m.winner_slot = (p % 2) as u8;
+                }
+
+                matches.push(m);
+            }
+        }
+
+        let mut m = BracketMatch::new(0);
+        m.side = BracketSide::GrandFinal;
+        m.winner_place = 1;
+        m.loser_place = 2;
+        matches.push(m.clone());
+        if bracket_reset {
+            m.round = 1;
+            matches.push(m);
+        }
+
+        self.size = size as u8;
+        self.players = players;
+        self.matches = matches;
+        self.placements = [None; PAID_PLACES];
+        self.byes = Vec::new();
+        self.grand_final = grand_final as u8;
+        self.reset_match = if bracket_reset { grand_final as u8 + 1 } else { NO_MATCH };
+        self.completed = false;
+
+        for index in 0..(size / 2) {
+            self.resolve(index)?;
+        }
+
+        Ok(())
+    }
+
+    /// Complete a ready match and route winner and loser; returns the loser
+    pub fn advance(&mut self, match_index: u8, winner: Pubkey) -> Result<Pubkey> {
+        require!(!self.completed, GameError::BracketAlreadyCompleted);
+
+        let m = self.matches
+            .get_mut(match_index as usize)
+            .ok_or(GameError::InvalidMatchIndex)?;
+        require!(m.status == MatchStatus::Ready, GameError::MatchNotReady);
+
+        let loser = match m.players {
+            [Some(a), Some(b)] if a == winner => b,
+            [Some(a), Some(b)] if b == winner => a,
+            _ => return Err(GameError::PlayerNotInMatch.into()),
+        };
+
+        m.winner = Some(winner);
+        m.status = MatchStatus::Completed;
+        let mut m = m.clone();
+
+        // A losers-bracket champion who takes the grand final forces the reset match;
+        // otherwise the reset match is never played
+        if match_index == self.grand_final && self.reset_match != NO_MATCH {
+            if m.players[1] == Some(winner) {
+                m.winner_to = self.reset_match;
+                m.winner_slot = 1;
+                m.loser_to = self.reset_match;
+                m.loser_slot = 0;
+                m.winner_place = 0;
+                m.loser_place = 0;
+            } else {
+                self.matches[self.reset_match as usize].status = MatchStatus::Completed;
+            }
+        }
+
+        self.route(m.winner_to, m.winner_slot, m.winner_place, Some(winner))?;
+        self.route(m.loser_to, m.loser_slot, m.loser_place, Some(loser))?;
+
+        self.completed = self.matches.iter().all(|m| m.status == MatchStatus::Completed);
+
+        Ok(loser)
+    }
+
+    /// Deliver a result (or an empty slot) to the next match and record any placement
+    fn route(&mut self, to: u8, slot: u8, place: u8, player: Option<Pubkey>) -> Result<()> {
+        if let Some(next) = self.matches.get_mut(to as usize) {
+            if player.is_some() {
+                next.players[slot as usize] = player;
+            }
+            next.pending = next.pending.saturating_sub(1);
+            if next.pending == 0 {
+                self.resolve(to as usize)?;
+            }
+        }
+
+        if let Some(player) = player {
+            if (1..=PAID_PLACES as u8).contains(&place) {
+                self.placements[place as usize - 1] = Some(player);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Called once every feed into a match has arrived. Two players make it ready; a lone
+    /// player advances on a bye; an empty match passes nothing on.
+    fn resolve(&mut self, index: usize) -> Result<()> {
+        let m = &mut self.matches[index];
+        let present: Vec<Pubkey> = m.players.iter().flatten().copied().collect();
+
+        if present.len() == 2 {
+            m.status = MatchStatus::Ready;
+            return Ok(());
+        }
+
+        m.status = MatchStatus::Completed;
+        let m = m.clone();
+
+        match present.first().copied() {
+            Some(player) => {
+                require!(!self.byes.contains(&player), GameError::DuplicateBye);
+                self.byes.push(player);
+                self.matches[index].winner = Some(player);
+
+                self.route(m.winner_to, m.winner_slot, m.winner_place, Some(player))?;
+                self.route(m.loser_to, m.loser_slot, m.loser_place, None)
+            }
+            None => {
+                self.route(m.winner_to, m.winner_slot, m.winner_place, None)?;
+                self.route(m.loser_to, m.loser_slot, m.loser_place, None)
+            }
+        }
+    }
+}
+
+/// Most recent (slot, hash) entry from the raw SlotHashes sysvar data
+pub fn latest_slot_hash(slot_hashes: &AccountInfo) -> Result<(u64, [u8; 32])> {
+    let data = slot_hashes.try_borrow_data()?;
+    // Layout: u64 entry count, then (u64 slot, [u8; 32] hash) entries, newest first
+    require!(data.len() >= 8 + 8 + 32, GameError::SlotHashesUnavailable);
+
+    let slot = u64::from_le_bytes(data[8..16].try_into().unwrap());
+    let hash: [u8; 32] = data[16..48].try_into().unwrap();
+    Ok((slot, hash))
+}
+
+/// sha256(slot_hash || game_id || for each player in join order: pubkey || joined_at)
+pub fn bracket_seed(slot_hash: &[u8; 32], game: &Game) -> [u8; 32] {
+    let game_id = game.id.to_le_bytes();
+    let joined_at: Vec<[u8; 8]> = game.players.iter().map(|p| p.joined_at.to_le_bytes()).collect();
+
+    let mut parts: Vec<&[u8]> = vec![slot_hash, &game_id];
+    for (stake, joined) in game.players.iter().zip(joined_at.iter()) {
+        parts.push(stake.player.as_ref());
+        parts.push(joined);
+    }
+    hashv(&parts).to_bytes()
+}
+
+/// Fisher-Yates shuffle where swap `i` uses sha256(seed || i as u64 le) mod (i + 1)
+pub fn shuffle_players(seed: &[u8; 32], mut players: Vec<Pubkey>) -> Vec<Pubkey> {
+    for i in (1..players.len()).rev() {
+        let digest = hashv(&[seed, &(i as u64).to_le_bytes()]).to_bytes();
+        let roll = u64::from_le_bytes(digest[..8].try_into().unwrap());
+        players.swap(i, (roll % (i as u64 + 1)) as usize);
+    }
+    players
+}
+
+/// Standard bracket order: seed `i` meets seed `size - 1 - i` and top seeds meet last
+pub fn seed_positions(size: usize) -> Vec<usize> {
+    let mut positions = vec![0];
+    while positions.len() < size {
+        let len = positions.len() * 2;
+        positions = positions
+            .iter()
+            .flat_map(|&s| [s, len - 1 - s])
+            .collect();
+    }
+    positions
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BracketMatch {
+    pub side: BracketSide,
+    pub round: u8,
+    pub players: [Option<Pubkey>; 2],
+    pub winner: Option<Pubkey>,
+    pub status: MatchStatus,
+    pub winner_to: u8,
+    pub winner_slot: u8,
+    pub loser_to: u8,
+    pub loser_slot: u8,
+    pub winner_place: u8,
+    pub loser_place: u8,
+    pub pending: u8,
+}
+
+impl BracketMatch {
+    pub const SIZE: usize = 1 + 1 + 2 * (1 + 32) + (1 + 32) + 1 + 7;
+
+    fn new(round: u8) -> Self {
+        Self {
+            side: BracketSide::Winners,
+            round,
+            players: [None, None],
+            winner: None,
+            status: MatchStatus::Pending,
+            winner_to: NO_MATCH,
+            winner_slot: 0,
+            loser_to: NO_MATCH,
+            loser_slot: 0,
+            winner_place: 0,
+            loser_place: 0,
+            pending: 2,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum BracketSide {
+    Winners,
+    Losers,
+    GrandFinal,
+    ThirdPlace,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum MatchStatus {
+    Pending,
+    Ready,
+    Completed,
+}
+
+#[account]
+pub struct Standings {
+    pub game_id: u64,
+    pub seed: [u8; 32],
+    pub seed_slot: u64,
+    pub round: u8,
+    pub total_rounds: u8,
+    pub entries: Vec<StandingEntry>,
+    pub pairings: Vec<Pairing>,
+    pub placements: [Option<Pubkey>; PAID_PLACES],
+    pub completed: bool,
+    pub bump: u8,
+    /// Paid the account's rent and gets it back when the game is settled
+    pub rent_payer: Pubkey,
+}
+
+impl Standings {
+    /// Account size for the standings of `game_type`: one entry per seat, and for Swiss one
+    /// pairing per two seats plus a bye; leagues keep no pairings here
+    pub fn space(game_type: &GameType) -> usize {
+        let size = game_type.max_players() as usize;
+        let pairings = match game_type {
+            GameType::Swiss { .. } => size.div_ceil(2),
+            _ => 0,
+        };
+        8 + 8 + 32 + 8 + 1 + 1
+            + (4 + size * StandingEntry::SIZE)
+            + (4 + pairings * Pairing::SIZE)
+            + PAID_PLACES * (1 + 32)
+            + 1 + 1
+            + 32
+    }
+
+    /// Enter players in seed order and pair the first round
+    pub fn start_swiss(&mut self, players: Vec<Pubkey>, rounds: u8) -> Result<()> {
+        require!(
+            (2..=MAX_SWISS_SIZE).contains(&players.len()),
+            GameError::InvalidTournamentSize
+        );
+        require!(
+            rounds >= 1 && rounds <= max_swiss_rounds(players.len()),
+            GameError::InvalidSwissRounds
+        );
+
+        self.entries = players.into_iter().map(StandingEntry::new).collect();
+        self.total_rounds = rounds;
+        self.round = 1;
+        self.placements = [None; PAID_PLACES];
+        self.completed = false;
+
+        self.pair_swiss_round()
+    }
+
+    /// Record a pairing result; pairs the next round or finalizes once the round is complete
+    pub fn record_swiss_result(&mut self, pairing_index: u8, winner: Pubkey) -> Result<Pubkey> {
+        require!(!self.completed, GameError::StandingsAlreadyFinal);
+
+        let pairing = self.pairings
+            .get(pairing_index as usize)
+            .cloned()
+            .ok_or(GameError::InvalidMatchIndex)?;
+        require!(pairing.winner == NO_PLAYER, GameError::MatchNotReady);
+
+        let (w, l) = if self.entries[pairing.a as usize].player == winner {
+            (pairing.a, pairing.b)
+        } else if pairing.b != NO_PLAYER && self.entries[pairing.b as usize].player == winner {
+            (pairing.b, pairing.a)
+        } else {
+            return Err(GameError::PlayerNotInMatch.into());
+        };
+
+        self.pairings[pairing_index as usize].winner = w;
+        self.credit_win(w as usize, l as usize);
+        let loser = self.entries[l as usize].player;
+
+        if self.pairings.iter().all(|p| p.winner != NO_PLAYER) {
+            if self.round == self.total_rounds {
+                self.finalize();
+            } else {
+                self.round += 1;
+                self.pair_swiss_round()?;
+            }
+        }
+
+        Ok(loser)
+    }
+
+    /// Enter league players in seed order; every pair plays once, in any order
+    pub fn start_league(&mut self, players: Vec<Pubkey>) -> Result<()> {
+        require!(
+            (2..=MAX_LEAGUE_SIZE).contains(&players.len()),
+            GameError::InvalidTournamentSize
+        );
+
+        self.entries = players.into_iter().map(StandingEntry::new).collect();
+        self.total_rounds = 0;
+        self.round = 0;
+        self.pairings = Vec::new();
+        self.placements = [None; PAID_PLACES];
+        self.completed = false;
+
+        Ok(())
+    }
+
+    /// Record the league pairing of entries `a < b`; finalizes once every pair has played
+    pub fn record_league_result(&mut self, a: u8, b: u8, winner: Pubkey) -> Result<Pubkey> {
+        require!(!self.completed, GameError::StandingsAlreadyFinal);
+        require!(a < b && (b as usize) < self.entries.len(), GameError::InvalidMatchIndex);
+
+        let (a, b) = (a as usize, b as usize);
+        require!(self.entries[a].opponents & (1 << b) == 0, GameError::MatchAlreadyPlayed);
+
+        let (w, l) = if self.entries[a].player == winner {
+            (a, b)
+        } else if self.entries[b].player == winner {
+            (b, a)
+        } else {
+            return Err(GameError::PlayerNotInMatch.into());
+        };
+
+        self.entries[a].opponents |= 1 << b;
+        self.entries[b].opponents |= 1 << a;
+        self.credit_win(w, l);
+
+        let n = self.entries.len();
+        let played: usize = self.entries.iter().map(|e| e.wins as usize).sum();
+        if played == n * (n - 1) / 2 {
+            self.finalize();
+        }
+
+        Ok(self.entries[l].player)
+    }
+
+    fn credit_win(&mut self, w: usize, l: usize) {
+        self.entries[w].points += 1;
+        self.entries[w].wins += 1;
+        self.entries[w].beaten |= 1 << l;
+        self.entries[l].losses += 1;
+    }
+
+    /// Pair players in score order (seed order within a score) without rematches. With an odd
+    /// field, the lowest-ranked player who has not had a bye sits out for a point. If no such
+    /// pairing is found within the step budget, the round is paired straight down the ranking
+    /// and rematches are allowed, so an active game can always continue.
+    fn pair_swiss_round(&mut self) -> Result<()> {
+        let mut order: Vec<usize> = (0..self.entries.len()).collect();
+        order.sort_by(|&a, &b| self.entries[b].points.cmp(&self.entries[a].points).then(a.cmp(&b)));
+
+        let bye_candidates: Vec<Option<usize>> = if order.len() % 2 == 1 {
+            order.iter().rev().filter(|&&i| !self.entries[i].had_bye).map(|&i| Some(i)).collect()
+        } else {
+            vec![None]
+        };
+
+        let opponents: Vec<u64> = self.entries.iter().map(|e| e.opponents).collect();
+        let mut budget = MAX_PAIRING_STEPS;
+
+        for bye in bye_candidates {
+            let remaining: Vec<usize> = order.iter().copied().filter(|&i| Some(i) != bye).collect();
+            let mut pairs = Vec::with_capacity(remaining.len() / 2);
+            if pair_without_rematches(&remaining, &opponents, &mut pairs, &mut budget) {
+                self.set_pairings(pairs, bye);
+                return Ok(());
+            }
+        }
+
+        let bye = if order.len() % 2 == 1 {
+            order.iter().rev().copied().find(|&i| !self.entries[i].had_bye).or(order.last().copied())
+        } else {
+            None
+        };
+        let pairs = order
+            .iter()
+            .copied()
+            .filter(|&i| Some(i) != bye)
+            .collect::<Vec<_>>()
+            .chunks(2)
+            .map(|pair| (pair[0], pair[1]))
+            .collect();
+        self.set_pairings(pairs, bye);
+        Ok(())
+    }
+
+    fn set_pairings(&mut self, pairs: Vec<(usize, usize)>, bye: Option<usize>) {
+        let mut pairings = Vec::with_capacity(pairs.len() + 1);
+        for (a, b) in pairs {
+            self.entries[a].opponents |= 1 << b;
+            self.entries[b].opponents |= 1 << a;
+            pairings.push(Pairing { a: a as u8, b: b as u8, winner: NO_PLAYER });
+        }
+        if let Some(i) = bye {
+            self.entries[i].had_bye = true;
+            self.entries[i].points += 1;
+            pairings.push(Pairing { a: i as u8, b: NO_PLAYER, winner: i as u8 });
+        }
+
+        self.pairings = pairings;
+    }
+
+    /// Rank by points, then Buchholz (sum of opponents' points), then Sonneborn-Berger
+    /// (sum of beaten opponents' points), then seed order. In a league everyone meets
+    /// everyone, so Buchholz cannot split a tie and Sonneborn-Berger decides it.
+    fn finalize(&mut self) {
+        let points: Vec<u16> = self.entries.iter().map(|e| e.points as u16).collect();
+        let sum_over = |mask: u64| -> u16 {
+            (0..points.len()).filter(|&i| mask & (1 << i) != 0).map(|i| points[i]).sum()
+        };
+        for entry in self.entries.iter_mut() {
+            entry.buchholz = sum_over(entry.opponents);
+            entry.sonneborn_berger = sum_over(entry.beaten);
+        }
+
+        let mut ranking: Vec<usize> = (0..self.entries.len()).collect();
+        ranking.sort_by(|&a, &b| {
+            let (ea, eb) = (&self.entries[a], &self.entries[b]);
+            eb.points
+                .cmp(&ea.points)
+                .then(eb.buchholz.cmp(&ea.buchholz))
+                .then(eb.sonneborn_berger.cmp(&ea.sonneborn_berger))
+                .then(a.cmp(&b))
+        });
+
+        for (place, &i) in ranking.iter().take(PAID_PLACES).enumerate() {
+            self.placements[place] = Some(self.entries[i].player);
+        }
+        self.completed = true;
+    }
+}
+
+/// Depth-first pairing of `remaining` (in rank order): the top player takes the highest-ranked
+/// opponent they have not met, backtracking when the rest of the field cannot be paired
+fn pair_without_rematches(
+    remaining: &[usize],
+    opponents: &[u64],
+    pairs: &mut Vec<(usize, usize)>,
+    budget: &mut u32,
+) -> bool {
+    let Some((&a, rest)) = remaining.split_first() else {
+        return true;
+    };
+
+    for (j, &b) in rest.iter().enumerate() {
+        if opponents[a] & (1 << b) != 0 {
+            continue;
+        }
+        if *budget == 0 {
+            return false;
+        }
+        *budget -= 1;
+
+        let others: Vec<usize> = rest.iter().enumerate().filter(|&(k, _)| k != j).map(|(_, &i)| i).collect();
+        pairs.push((a, b));
+        if pair_without_rematches(&others, opponents, pairs, budget) {
+            return true;
+        }
+        pairs.pop();
+    }
+
+    false
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct StandingEntry {
+    pub player: Pubkey,
+    pub points: u8,
+    pub wins: u8,
+    pub losses: u8,
+    pub had_bye: bool,
+    pub opponents: u64,
+    pub beaten: u64,
+    pub buchholz: u16,
+    pub sonneborn_berger: u16,
+}
+
+impl StandingEntry {
+    pub const SIZE: usize = 32 + 1 + 1 + 1 + 1 + 8 + 8 + 2 + 2;
+
+    fn new(player: Pubkey) -> Self {
+        Self {
+            player,
+            points: 0,
+            wins: 0,
+            losses: 0,
+            had_bye: false,
+            opponents: 0,
+            beaten: 0,
+            buchholz: 0,
+            sonneborn_berger: 0,
+        }
+    }
+}
+
+#[account]
+pub struct LeagueMatch {
+    pub game_id: u64,
+    pub player_a: Pubkey,
+    pub player_b: Pubkey,
+    pub winner: Pubkey,
+    pub recorded_slot: u64,
+    pub bump: u8,
+}
+
+impl LeagueMatch {
+    pub const SIZE: usize = 8 + 8 + 32 + 32 + 32 + 8 + 1;
+}
+
+/// Indices into `Standings::entries`; `b == NO_PLAYER` is a bye, `winner == NO_PLAYER` is unplayed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Pairing {
+    pub a: u8,
+    pub b: u8,
+    pub winner: u8,
+}
+
+impl Pairing {
+    pub const SIZE: usize = 3;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PlayerStake {
+    pub player: Pubkey,
+    pub amount: u64,
+    pub joined_at: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct StakeTerms {
+    /// Fee rate charged on entry, after any volume discount
+    pub fee_bps: u16,
+    /// Funded the stake and receives its refund; the player unless the entry was sponsored
+    /// or paid from the player's balance
+    pub payer: Pubkey,
+    /// Paid from the player's balance (`payer`), which then also receives the winnings
+    pub from_balance: bool,
+}
+
+impl StakeTerms {
+    pub const SIZE: usize = 2 + 32 + 1;
 
-        if let Some(fee) = fee_bps {
-            require!(fee <= 10000, GameError::InvalidFeeBps);
-            config.fee_bps = fee;
-        }
+    pub fn fee_paid(&self, amount: u64) -> u64 {
+        (amount as u128 * self.fee_bps as u128 / 10000) as u64
+    }
+}
 
-        if let (Some(first), Some(second), Some(third)) = (first_place_percentage, second_place_percentage, third_place_percentage) {
-            require!(first + second + third == 100, GameError::InvalidPrizeDistribution);
-            config.first_place_percentage = first;
-            config.second_place_percentage = second;
-            config.third_place_percentage = third;
-        }
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum GameType {
+    OneVsOne,
+    Tournament { size: u8 },
+    DoubleElimination { size: u8, bracket_reset: bool },
+    Swiss { size: u8, rounds: u8 },
+    League { size: u8 },
+}
 
-        if let Some(receiver) = fee_receiver {
-            config.fee_receiver = receiver;
+impl GameType {
+    /// Seats in the game, which also sizes its accounts
+    pub fn max_players(&self) -> u8 {
+        match self {
+            GameType::OneVsOne => 2,
+            GameType::Tournament { size }
+            | GameType::DoubleElimination { size, .. }
+            | GameType::Swiss { size, .. }
+            | GameType::League { size } => *size,
         }
+    }
 
-        Ok(())
+    pub fn uses_bracket(&self) -> bool {
+        matches!(self, GameType::Tournament { .. } | GameType::DoubleElimination { .. })
+    }
+
+    pub fn uses_standings(&self) -> bool {
+        matches!(self, GameType::Swiss { .. } | GameType::League { .. })
     }
 }
 
-// Helper function removed - no longer needed since we don't track player states
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum GameTier {
+    PointOne,
+    PointTwoFive,
+    PointFive,
+    One,
+}
 
-// Account structs
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(
-        init,
-        payer = admin,
-        space = GameConfig::SIZE,
-        seeds = [b"game_config"],
-        bump
-    )]
-    pub game_config: Account<'info, GameConfig>,
-    
-    #[account(mut)]
-    pub admin: Signer<'info>,
-    
-    /// CHECK: Fee receiver can be any account
-    pub fee_receiver: UncheckedAccount<'info>,
-    
-    pub system_program: Program<'info, System>,
+impl GameTier {
+    pub fn to_lamports(&self) -> u64 {
+        match self {
+            GameTier::PointOne => 100_000_000,      // 0.1 SOL
+            GameTier::PointTwoFive => 250_000_000,  // 0.25 SOL
+            GameTier::PointFive => 500_000_000,     // 0.5 SOL
+            GameTier::One => 1_000_000_000,         // 1.0 SOL
+        }
+    }
 }
 
-#[derive(Accounts)]
-pub struct CreateGame<'info> {
-    #[account(
-        init,
-        payer = creator,
-        space = Game::SIZE,
-        seeds = [b"game", game_config.next_game_id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub game: Account<'info, Game>,
-    
-    #[account(mut, seeds = [b"game_config"], bump)]
-    pub game_config: Account<'info, GameConfig>,
-    
-    #[account(mut)]
-    pub creator: Signer<'info>,
-    
-    #[account(mut, seeds = [b"vault"], bump)]
-    pub vault: SystemAccount<'info>,
-    
-    #[account(mut)]
-    /// CHECK: Fee receiver is validated in config
-    pub fee_receiver: UncheckedAccount<'info>,
-    
-    pub system_program: Program<'info, System>,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum GameStatus {
+    WaitingForPlayers,
+    Active,
+    Completed,
+    Cancelled,
 }
 
-#[derive(Accounts)]
-#[instruction(game_id: u64)]
-pub struct JoinGame<'info> {
-    #[account(
-        mut,
-        seeds = [b"game", game_id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub game: Account<'info, Game>,
-    
-    #[account(seeds = [b"game_config"], bump)]
-    pub game_config: Account<'info, GameConfig>,
-    
-    #[account(mut)]
-    pub player: Signer<'info>,
-    
-    #[account(mut, seeds = [b"vault"], bump)]
-    pub vault: SystemAccount<'info>,
-    
-    #[account(mut)]
-    /// CHECK: Fee receiver is validated in config
-    pub fee_receiver: UncheckedAccount<'info>,
-    
-    pub system_program: Program<'info, System>,
+/// Settings that `update_config` and `execute_config_change` can touch
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ConfigSnapshot {
+    pub fee_bps: u16,
+    pub prize_percentages: [u8; 3],
+    pub fee_receiver: Pubkey,
+    pub config_timelock_secs: i64,
+    pub max_gas_fee_lamports: u64,
+    pub gas_reimbursement: Pubkey,
+    pub max_active_games_per_player: u16,
+    pub referral_share_bps: u16,
+    pub fee_tiers: [FeeTier; MAX_FEE_TIERS],
+    pub balance_withdrawal_cooldown_secs: i64,
 }
 
-#[derive(Accounts)]
-#[instruction(game_id: u64)]
-pub struct AdminWithdrawPlayer<'info> {
-    #[account(
-        mut,
-        seeds = [b"game", game_id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub game: Account<'info, Game>,
-    
-    #[account(seeds = [b"game_config"], bump)]
-    pub game_config: Account<'info, GameConfig>,
-    
-    pub admin: Signer<'info>,
-    
-    #[account(mut, seeds = [b"vault"], bump)]
-    pub vault: SystemAccount<'info>,
+/// Discounted fee for players whose lifetime settled volume reaches `min_volume_lamports`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq)]
+pub struct FeeTier {
+    /// 0 marks an unused slot
+    pub min_volume_lamports: u64,
+    pub fee_bps: u16,
 }
 
-#[derive(Accounts)]
-#[instruction(game_id: u64)]
-pub struct AdminCancelGame<'info> {
-    #[account(
-        mut,
-        seeds = [b"game", game_id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub game: Account<'info, Game>,
-    
-    #[account(seeds = [b"game_config"], bump)]
-    pub game_config: Account<'info, GameConfig>,
-    
-    pub admin: Signer<'info>,
-    
-    #[account(mut, seeds = [b"vault"], bump)]
-    pub vault: SystemAccount<'info>,
+impl FeeTier {
+    pub const SIZE: usize = 8 + 2;
+
+    pub fn is_active(&self) -> bool {
+        self.min_volume_lamports > 0
+    }
 }
 
-#[derive(Accounts)]
-#[instruction(game_id: u64)]
-pub struct AdminPayoutWinners<'info> {
-    #[account(
-        mut,
-        seeds = [b"game", game_id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub game: Account<'info, Game>,
-    
-    #[account(seeds = [b"game_config"], bump)]
-    pub game_config: Account<'info, GameConfig>,
-    
-    pub admin: Signer<'info>,
-    
-    #[account(mut, seeds = [b"vault"], bump)]
-    pub vault: SystemAccount<'info>,
+/// Ring buffer of the last `ADMIN_LOG_CAPACITY` privileged actions
+#[account]
+pub struct AdminActionLog {
+    /// Slot the next entry is written to once the buffer is full
+    pub next_index: u16,
+    pub total_actions: u64,
+    pub entries: Vec<AdminActionEntry>,
+    pub bump: u8,
 }
 
-#[derive(Accounts)]
-pub struct AdminEmergencyWithdraw<'info> {
-    #[account(seeds = [b"game_config"], bump)]
-    pub game_config: Account<'info, GameConfig>,
-    
-    pub admin: Signer<'info>,
-    
-    #[account(mut, seeds = [b"vault"], bump)]
-    pub vault: SystemAccount<'info>,
+impl AdminActionLog {
+    pub const SIZE: usize = 8 + 2 + 8 + (4 + ADMIN_LOG_CAPACITY * AdminActionEntry::SIZE) + 1;
+
+    pub fn record(
+        &mut self,
+        actor: Pubkey,
+        action: AdminAction,
+        game_id: u64,
+        amount: u64,
+        gas_deducted: u64,
+    ) -> Result<()> {
+        let entry = AdminActionEntry {
+            actor,
+            action,
+            game_id,
+            amount,
+            gas_deducted,
+            slot: Clock::get()?.slot,
+        };
+
+        if self.entries.len() < ADMIN_LOG_CAPACITY {
+            self.entries.push(entry);
+        } else {
+            self.entries[self.next_index as usize] = entry;
+        }
+        self.next_index = ((self.next_index as usize + 1) % ADMIN_LOG_CAPACITY) as u16;
+        self.total_actions += 1;
+
+        Ok(())
+    }
 }
 
-#[derive(Accounts)]
-pub struct UpdateConfig<'info> {
-    #[account(mut, seeds = [b"game_config"], bump)]
-    pub game_config: Account<'info, GameConfig>,
-    
-    pub admin: Signer<'info>,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AdminActionEntry {
+    pub actor: Pubkey,
+    pub action: AdminAction,
+    /// 0 for actions that are not tied to a game
+    pub game_id: u64,
+    /// Lamports moved, or the new value for config actions (fee bps, pause flags)
+    pub amount: u64,
+    pub gas_deducted: u64,
+    pub slot: u64,
 }
 
-// Data structures
+impl AdminActionEntry {
+    pub const SIZE: usize = 32 + 1 + 8 + 8 + 8 + 8;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
+pub enum AdminAction {
+    StartGame,
+    WithdrawPlayer,
+    CancelGame,
+    PayoutWinners,
+    EmergencyWithdraw,
+    SetPaused,
+    UpdateConfig,
+    QueueConfigChange,
+    ExecuteConfigChange,
+    CancelConfigChange,
+    AcceptAuthority,
+    ExecuteProposal,
+    BlockWallet,
+    UnblockWallet,
+    AdvanceWinner,
+    RecordSwissResult,
+    RecordLeagueResult,
+    ProposeAuthority,
+    CancelAuthorityProposal,
+    CreateMultisig,
+    CreateProposal,
+    ApproveProposal,
+    MigrateConfig,
+    ResyncActiveGames,
+}
+
+/// Config change waiting out the timelock; one at a time
 #[account]
-pub struct GameConfig {
-    pub admin: Pubkey,
-    pub fee_receiver: Pubkey,
-    pub fee_bps: u16,
-    pub first_place_percentage: u8,
-    pub second_place_percentage: u8,
-    pub third_place_percentage: u8,
-    pub next_game_id: u64,
+pub struct PendingConfigChange {
+    pub proposer: Pubkey,
+    pub fee_bps: Option<u16>,
+    pub prize_percentages: Option<[u8; 3]>,
+    pub fee_receiver: Option<Pubkey>,
+    pub config_timelock_secs: Option<i64>,
+    pub max_gas_fee_lamports: Option<u64>,
+    pub gas_reimbursement: Option<Pubkey>,
+    pub max_active_games_per_player: Option<u16>,
+    pub referral_share_bps: Option<u16>,
+    pub fee_tiers: Option<[FeeTier; MAX_FEE_TIERS]>,
+    pub balance_withdrawal_cooldown_secs: Option<i64>,
+    pub queued_at: i64,
+    pub executable_at: i64,
     pub bump: u8,
 }
 
-impl GameConfig {
-    pub const SIZE: usize = 8 + 32 + 32 + 2 + 1 + 1 + 1 + 8 + 1;
+impl PendingConfigChange {
+    pub const SIZE: usize = 8 + 32 + (1 + 2) + (1 + 3) + (1 + 32) + (1 + 8) + (1 + 8) + (1 + 32) + (1 + 2) + (1 + 2) + (1 + MAX_FEE_TIERS * FeeTier::SIZE) + (1 + 8) + 8 + 8 + 1;
+
+    pub fn validate(&self) -> Result<()> {
+        if let Some(fee) = self.fee_bps {
+            require!(fee <= 10000, GameError::InvalidFeeBps);
+        }
+        if let Some([first, second, third]) = self.prize_percentages {
+            require!(
+                first as u16 + second as u16 + third as u16 == 100,
+                GameError::InvalidPrizeDistribution
+            );
+        }
+        if let Some(timelock) = self.config_timelock_secs {
+            require!(
+                (0..=MAX_CONFIG_TIMELOCK_SECS).contains(&timelock),
+                GameError::InvalidTimelock
+            );
+        }
+        if let Some(limit) = self.max_active_games_per_player {
+            require!(limit >= 1, GameError::InvalidActiveGameLimit);
+        }
+        if let Some(share) = self.referral_share_bps {
+            require!(share <= 10000, GameError::InvalidReferralShare);
+        }
+        if let Some(tiers) = self.fee_tiers {
+            validate_fee_tiers(&tiers)?;
+        }
+        if let Some(cooldown) = self.balance_withdrawal_cooldown_secs {
+            require!(
+                (0..=MAX_BALANCE_WITHDRAWAL_COOLDOWN_SECS).contains(&cooldown),
+                GameError::InvalidWithdrawalCooldown
+            );
+        }
+        Ok(())
+    }
 }
 
+/// M-of-N approval set for dangerous admin actions
 #[account]
-pub struct Game {
-    pub id: u64,
-    pub creator: Pubkey,
-    pub game_type: GameType,
-    pub tier: GameTier,
-    pub stake_per_player: u64,
-    pub max_players: u8,
-    pub status: GameStatus,
-    pub players: Vec<PlayerStake>,
-    pub total_pot: u64,
-    pub created_at: i64,
+pub struct Multisig {
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+    /// Bumped on every signer set change; older proposals can no longer pass
+    pub version: u64,
+    pub proposal_count: u64,
     pub bump: u8,
 }
 
-// impl Game {
-//     pub const SIZE: usize = 8 + 8 + 32 + (1 + 1) + 1 + 8 + 1 + 1 + (4 + 16 * (32 + 8 + 8)) + 8 + 8 + 1;
-// }
+impl Multisig {
+    pub const SIZE: usize = 8 + (4 + MAX_MULTISIG_SIGNERS * 32) + 1 + 8 + 8 + 1;
 
+    pub fn signer_index(&self, key: &Pubkey) -> Result<usize> {
+        self.signers
+            .iter()
+            .position(|s| s == key)
+            .ok_or(GameError::NotAnApprover.into())
+    }
+}
 
-impl Game {
-    pub const SIZE: usize = 8 + 8 + 32 + 2 + 1 + 8 + 1 + 1 + (4 + 16 * (32 + 8 + 8)) + 8 + 8 + 1 + 500; 
+#[account]
+pub struct Proposal {
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub action: ProposalAction,
+    /// Bit i set when `Multisig::signers[i]` approved
+    pub approvals: u16,
+    pub signer_set_version: u64,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub executed: bool,
+    pub bump: u8,
 }
 
-// PlayerState struct removed - backend handles all player eligibility
+impl Proposal {
+    pub const SIZE: usize = 8 + 8 + 32 + ProposalAction::SIZE + 2 + 8 + 8 + 8 + 1 + 1;
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct PlayerStake {
-    pub player: Pubkey,
-    pub amount: u64,
-    pub joined_at: i64,
+    /// Proposal can still collect approvals or be executed
+    pub fn check_live(&self, multisig: &Multisig) -> Result<()> {
+        require!(!self.executed, GameError::ProposalAlreadyExecuted);
+        require!(self.signer_set_version == multisig.version, GameError::SignerSetChanged);
+        require!(
+            Clock::get()?.unix_timestamp <= self.expires_at,
+            GameError::ProposalExpired
+        );
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum ProposalAction {
+    /// Move funds out of the vault, including stakes owed to players
+    WithdrawLiabilities { recipient: Pubkey, amount: u64 },
+    /// Hand a role to a new key without the two-step handshake
+    SetAuthority { role: AdminRole, new_authority: Pubkey },
+    /// Make the pending config change queued at `queued_at` executable immediately
+    OverrideTimelock { queued_at: i64 },
+    /// Replace the approval set
+    SetSigners { signers: Vec<Pubkey>, threshold: u8 },
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
-pub enum GameType {
-    OneVsOne,
-    Tournament { size: u8 },
+impl ProposalAction {
+    /// Largest variant (`SetSigners` with a full signer set)
+    pub const SIZE: usize = 1 + (4 + MAX_MULTISIG_SIGNERS * 32) + 1;
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
-pub enum GameTier {
-    PointOne,
-    PointTwoFive,
-    PointFive,
-    One,
+pub enum AdminRole {
+    Config,
+    Settlement,
+    Pauser,
+    Treasury,
+    Blocklist,
 }
 
-impl GameTier {
-    pub fn to_lamports(&self) -> u64 {
+impl AdminRole {
+    /// PDA seed byte for per-role accounts
+    pub fn seed(&self) -> u8 {
         match self {
-            GameTier::PointOne => 100_000_000,      // 0.1 SOL
-            GameTier::PointTwoFive => 250_000_000,  // 0.25 SOL
-            GameTier::PointFive => 500_000_000,     // 0.5 SOL
-            GameTier::One => 1_000_000_000,         // 1.0 SOL
+            AdminRole::Config => 0,
+            AdminRole::Settlement => 1,
+            AdminRole::Pauser => 2,
+            AdminRole::Treasury => 3,
+            AdminRole::Blocklist => 4,
         }
     }
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
-pub enum GameStatus {
-    WaitingForPlayers,
-    Active,
-    Completed,
-    Cancelled,
+/// Marks a wallet that may not create or join games; exists only while the block is in force
+#[account]
+pub struct Blocked {
+    pub wallet: Pubkey,
+    /// Paid the rent and gets it back on unblock
+    pub blocked_by: Pubkey,
+    pub blocked_at: i64,
+    pub bump: u8,
+}
+
+impl Blocked {
+    pub const SIZE: usize = 8 + 32 + 32 + 8 + 1;
+}
+
+#[account]
+pub struct AuthorityProposal {
+    pub role: AdminRole,
+    pub proposer: Pubkey,
+    pub new_authority: Pubkey,
+    pub proposed_at: i64,
+    pub bump: u8,
+}
+
+impl AuthorityProposal {
+    pub const SIZE: usize = 8 + 1 + 32 + 32 + 8 + 1;
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -637,21 +4554,93 @@ pub struct WinnerPayout {
     pub place: u8,
 }
 
+/// A winner's place and the lamports actually paid, after gas
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Prize {
+    pub player: Pubkey,
+    pub place: u8,
+    pub amount: u64,
+}
+
+impl Prize {
+    pub const SIZE: usize = 32 + 1 + 8;
+}
+
 // Events
 #[event]
 pub struct GameCreated {
     pub game_id: u64,
     pub creator: Pubkey,
+    pub payer: Pubkey,
     pub game_type: GameType,
     pub tier: GameTier,
     pub stake_amount: u64,
+    pub fee_bps: u16,
 }
 
 #[event]
 pub struct PlayerJoined {
     pub game_id: u64,
     pub player: Pubkey,
+    pub payer: Pubkey,
     pub players_count: u8,
+    pub fee_bps: u16,
+}
+
+#[event]
+pub struct BalanceDeposited {
+    pub player: Pubkey,
+    pub amount: u64,
+    pub balance: u64,
+}
+
+#[event]
+pub struct BalanceWithdrawn {
+    pub player: Pubkey,
+    pub amount: u64,
+    pub balance: u64,
+}
+
+#[event]
+pub struct RematchCreated {
+    pub game_id: u64,
+    pub previous_game_id: u64,
+    pub creator: Pubkey,
+    pub opponent: Pubkey,
+}
+
+#[event]
+pub struct ReferrerBound {
+    pub player: Pubkey,
+    pub referrer: Pubkey,
+}
+
+#[event]
+pub struct ReferralFeeShared {
+    pub game_id: u64,
+    pub player: Pubkey,
+    pub referrer: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ReferralRewardsClaimed {
+    pub referrer: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct SessionKeyRegistered {
+    pub player: Pubkey,
+    pub session_key: Pubkey,
+    pub game_ids: Vec<u64>,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct SessionKeyRevoked {
+    pub player: Pubkey,
+    pub session_key: Pubkey,
 }
 
 #[event]
@@ -664,19 +4653,182 @@ pub struct GameStarted {
 pub struct PlayerWithdrawn {
     pub game_id: u64,
     pub player: Pubkey,
+    pub refunded_to: Pubkey,
     pub refund_amount: u64,
+    pub gas_deducted: u64,
+    /// Entry fee the player paid, which is not refunded
+    pub fee_paid: u64,
+    pub admin: Pubkey,
+    pub remaining_players: u8,
 }
 
 #[event]
 pub struct GameCancelled {
     pub game_id: u64,
     pub refunded_players: u8,
+    pub gas_deducted: u64,
+    pub total_refunded: u64,
+    pub admin: Pubkey,
 }
 
 #[event]
 pub struct GameCompleted {
     pub game_id: u64,
     pub winners: Vec<WinnerPayout>,
+    pub gas_deducted: u64,
+    pub total_paid: u64,
+    pub admin: Pubkey,
+}
+
+#[event]
+pub struct BracketSeeded {
+    pub game_id: u64,
+    pub seed: [u8; 32],
+    pub seed_slot: u64,
+}
+
+#[event]
+pub struct MatchAdvanced {
+    pub game_id: u64,
+    pub match_index: u8,
+    pub winner: Pubkey,
+    pub loser: Pubkey,
+}
+
+#[event]
+pub struct BracketCompleted {
+    pub game_id: u64,
+    pub placements: Vec<Pubkey>,
+}
+
+#[event]
+pub struct SwissRoundPaired {
+    pub game_id: u64,
+    pub round: u8,
+    pub pairings: Vec<Pairing>,
+}
+
+#[event]
+pub struct SwissResultRecorded {
+    pub game_id: u64,
+    pub round: u8,
+    pub pairing_index: u8,
+    pub winner: Pubkey,
+    pub loser: Pubkey,
+}
+
+#[event]
+pub struct LeagueResultRecorded {
+    pub game_id: u64,
+    pub a: u8,
+    pub b: u8,
+    pub winner: Pubkey,
+    pub loser: Pubkey,
+}
+
+#[event]
+pub struct StandingsFinalized {
+    pub game_id: u64,
+    pub placements: Vec<Pubkey>,
+}
+
+#[event]
+pub struct AuthorityProposed {
+    pub role: AdminRole,
+    pub proposer: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityProposalCancelled {
+    pub role: AdminRole,
+    pub cancelled_by: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub updated_by: Pubkey,
+    pub old: ConfigSnapshot,
+    pub new: ConfigSnapshot,
+}
+
+#[event]
+pub struct ConfigMigrated {
+    pub from_version: u8,
+    pub to_version: u8,
+}
+
+#[event]
+pub struct ConfigChangeQueued {
+    pub fee_bps: Option<u16>,
+    pub prize_percentages: Option<[u8; 3]>,
+    pub fee_receiver: Option<Pubkey>,
+    pub config_timelock_secs: Option<i64>,
+    pub max_gas_fee_lamports: Option<u64>,
+    pub gas_reimbursement: Option<Pubkey>,
+    pub max_active_games_per_player: Option<u16>,
+    pub referral_share_bps: Option<u16>,
+    pub fee_tiers: Option<[FeeTier; MAX_FEE_TIERS]>,
+    pub balance_withdrawal_cooldown_secs: Option<i64>,
+    pub executable_at: i64,
+}
+
+#[event]
+pub struct ConfigChangeExecuted {
+    pub fee_bps: Option<u16>,
+    pub prize_percentages: Option<[u8; 3]>,
+    pub fee_receiver: Option<Pubkey>,
+    pub config_timelock_secs: Option<i64>,
+    pub max_gas_fee_lamports: Option<u64>,
+    pub gas_reimbursement: Option<Pubkey>,
+    pub max_active_games_per_player: Option<u16>,
+    pub referral_share_bps: Option<u16>,
+    pub fee_tiers: Option<[FeeTier; MAX_FEE_TIERS]>,
+    pub balance_withdrawal_cooldown_secs: Option<i64>,
+}
+
+#[event]
+pub struct ConfigChangeCancelled {
+    pub queued_at: i64,
+}
+
+#[event]
+pub struct PauseUpdated {
+    pub previous: u8,
+    pub paused: u8,
+}
+
+#[event]
+pub struct WalletBlocked {
+    pub wallet: Pubkey,
+    pub blocked_by: Pubkey,
+}
+
+#[event]
+pub struct WalletUnblocked {
+    pub wallet: Pubkey,
+    pub unblocked_by: Pubkey,
+}
+
+#[event]
+pub struct ActiveGamesResynced {
+    pub player: Pubkey,
+    pub previous: u16,
+    pub active_games: u16,
+}
+
+#[event]
+pub struct ActiveGameReleased {
+    pub game_id: u64,
+    pub player: Pubkey,
+}
+
+#[event]
+pub struct RoleUpdated {
+    pub role: AdminRole,
+    pub previous: Pubkey,
+    pub new_authority: Pubkey,
 }
 
 #[event]
@@ -685,6 +4837,45 @@ pub struct EmergencyWithdraw {
     pub amount: u64,
 }
 
+/// Funds owed to players left the vault
+#[event]
+pub struct LiabilitiesWithdrawn {
+    pub proposal_id: u64,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub outstanding_liabilities: u64,
+    pub uncovered_liabilities: u64,
+}
+
+#[event]
+pub struct MultisigUpdated {
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+    pub version: u64,
+}
+
+#[event]
+pub struct ProposalCreated {
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub action: ProposalAction,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct ProposalApproved {
+    pub proposal_id: u64,
+    pub signer: Pubkey,
+    pub approvals: u8,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub proposal_id: u64,
+    pub executor: Pubkey,
+    pub approvals: u8,
+}
+
 // Error codes
 #[error_code]
 pub enum GameError {
@@ -722,4 +4913,575 @@ pub enum GameError {
     InsufficientVaultBalance,
     #[msg("Invalid winner place")]
     InvalidPlace,
-}
\ No newline at end of file
+    #[msg("Field size is not valid for this game type")]
+    InvalidTournamentSize,
+    #[msg("Bracket account missing or not expected for this game type")]
+    InvalidBracketAccount,
+    #[msg("Invalid bracket match index")]
+    InvalidMatchIndex,
+    #[msg("Bracket match is not ready")]
+    MatchNotReady,
+    #[msg("Player not in bracket match")]
+    PlayerNotInMatch,
+    #[msg("Bracket already completed")]
+    BracketAlreadyCompleted,
+    #[msg("Bracket not completed")]
+    BracketNotCompleted,
+    #[msg("Winners do not match bracket placements")]
+    PlacementMismatch,
+    #[msg("SlotHashes sysvar has no entries")]
+    SlotHashesUnavailable,
+    #[msg("Not enough players to start")]
+    NotEnoughPlayers,
+    #[msg("Player already received a bye")]
+    DuplicateBye,
+    #[msg("Standings account missing or not expected for this game type")]
+    InvalidStandingsAccount,
+    #[msg("Swiss rounds must be between 1 and ceil(log2(size)) + 2, at most half the field")]
+    InvalidSwissRounds,
+    #[msg("Standings already final")]
+    StandingsAlreadyFinal,
+    #[msg("Standings not final")]
+    StandingsNotFinal,
+    #[msg("League pairing already played")]
+    MatchAlreadyPlayed,
+    #[msg("This instruction is paused")]
+    Paused,
+    #[msg("Unknown pause flag")]
+    InvalidPauseFlags,
+    #[msg("Timelock must be between 0 and 30 days")]
+    InvalidTimelock,
+    #[msg("Config change timelock has not elapsed")]
+    TimelockNotElapsed,
+    #[msg("Only fee decreases apply immediately; queue other changes")]
+    FeeIncreaseRequiresTimelock,
+    #[msg("Config change is empty")]
+    EmptyConfigChange,
+    #[msg("Amount exceeds the vault surplus above outstanding liabilities")]
+    ExceedsVaultSurplus,
+    #[msg("Signer set must be 2 to 10 unique keys with a threshold of at least 2 and at most its size")]
+    InvalidSignerSet,
+    #[msg("Signer is not part of the approval set")]
+    NotAnApprover,
+    #[msg("Proposal already approved by this signer")]
+    AlreadyApproved,
+    #[msg("Proposal lifetime must be between 1 second and 7 days")]
+    InvalidProposalLifetime,
+    #[msg("Proposal has expired")]
+    ProposalExpired,
+    #[msg("Proposal already executed")]
+    ProposalAlreadyExecuted,
+    #[msg("Not enough approvals")]
+    ThresholdNotMet,
+    #[msg("Approval set changed after the proposal was created")]
+    SignerSetChanged,
+    #[msg("Pending config change does not match the proposal")]
+    PendingChangeMismatch,
+    #[msg("Gas fee exceeds the configured maximum")]
+    GasFeeTooHigh,
+    #[msg("Account is not a game config")]
+    InvalidConfigAccount,
+    #[msg("Config is already at the current version")]
+    ConfigAlreadyCurrent,
+    #[msg("Winner account does not match the winner")]
+    WinnerAccountMismatch,
+    #[msg("Invalid player profile account")]
+    InvalidProfileAccount,
+    #[msg("Player is already in the maximum number of active games")]
+    TooManyActiveGames,
+    #[msg("Active game limit must be at least 1")]
+    InvalidActiveGameLimit,
+    #[msg("Referral share cannot exceed 100% of the fee")]
+    InvalidReferralShare,
+    #[msg("Referrer is the player or does not match the bound referrer")]
+    InvalidReferrer,
+    #[msg("Missing referrer profile account")]
+    MissingReferrerProfile,
+    #[msg("No referral rewards to claim")]
+    NoReferralRewards,
+    #[msg("Fee tiers must be contiguous, with rising volumes and non-increasing fees")]
+    InvalidFeeTiers,
+    #[msg("Wallet is blocked from creating or joining games")]
+    WalletBlocked,
+    #[msg("Refund account does not match the stake's payer")]
+    RefundAccountMismatch,
+    #[msg("Session key must be a new key scoped to 1-4 games for at most 24 hours")]
+    InvalidSessionKey,
+    #[msg("Signer is not the player or a valid session key for this game")]
+    SessionNotAuthorized,
+    #[msg("Rematches need a completed 1v1 you played that has no rematch yet")]
+    RematchNotAvailable,
+    #[msg("This game's open seat is reserved for another player")]
+    GameReserved,
+    #[msg("Balance withdrawals are still in their cooldown after the last deposit")]
+    BalanceCooldownActive,
+    #[msg("Balance withdrawal cooldown cannot exceed 7 days")]
+    InvalidWithdrawalCooldown,
+    #[msg("Double elimination has no byes; start it early only with 4 or 8 players")]
+    DoubleEliminationByes,
+    #[msg("Game already completed")]
+    GameAlreadyCompleted,
+    #[msg("Every approver must co-sign the multisig setup")]
+    MissingSignerConsent,
+    #[msg("Game is not open and the player's slot in it was already released")]
+    GameNotOpen,
+    #[msg("Game account passed more than once")]
+    DuplicateGameAccount,
+    #[msg("Cancelling a rematch needs the game it was opened from")]
+    MissingPreviousGame,
+    #[msg("Game is neither completed nor cancelled")]
+    GameNotSettled,
+    #[msg("Player's profile was already released for this game")]
+    ProfileAlreadyReleased,
+    #[msg("Fee receiver does not match the config")]
+    FeeReceiverMismatch,
+    #[msg("Rent payer does not match the bracket or standings")]
+    RentPayerMismatch,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Config bytes as written by the original, unversioned program
+    fn legacy_config(admin: Pubkey, fee_receiver: Pubkey) -> Vec<u8> {
+        let mut data = GameConfig::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(admin.as_ref());
+        data.extend_from_slice(fee_receiver.as_ref());
+        data.extend_from_slice(&50u16.to_le_bytes());
+        data.extend_from_slice(&[50, 30, 20]);
+        data.extend_from_slice(&7u64.to_le_bytes());
+        data.push(254);
+        assert_eq!(data.len(), GameConfig::LEGACY_SIZE);
+        data
+    }
+
+    /// What `migrate_config` sees after the account has been reallocated
+    fn zero_extended(mut data: Vec<u8>) -> Vec<u8> {
+        data.resize(GameConfig::SIZE, 0);
+        data
+    }
+
+    #[test]
+    fn migrates_legacy_config_with_defaults() {
+        let admin = Pubkey::new_unique();
+        let fee_receiver = Pubkey::new_unique();
+        let mut data = zero_extended(legacy_config(admin, fee_receiver));
+
+        let from_version = migrate_config_data(&mut data, 1_000).unwrap();
+        assert_eq!(from_version, 0);
+
+        let config = GameConfig::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+
+        // Existing settings survive
+        assert_eq!(config.admin, admin);
+        assert_eq!(config.fee_receiver, fee_receiver);
+        assert_eq!(config.fee_bps, 50);
+        assert_eq!(
+            [config.first_place_percentage, config.second_place_percentage, config.third_place_percentage],
+            [50, 30, 20]
+        );
+        assert_eq!(config.next_game_id, 7);
+        assert_eq!(config.bump, 254);
+
+        // New fields get defaults
+        assert_eq!(config.settlement_authority, admin);
+        assert_eq!(config.pauser, admin);
+        assert_eq!(config.treasury_authority, admin);
+        assert_eq!(config.blocklist_authority, admin);
+        assert_eq!(config.paused, 0);
+        assert_eq!(config.config_timelock_secs, DEFAULT_CONFIG_TIMELOCK_SECS);
+        assert_eq!(config.outstanding_liabilities, 1_000);
+        assert_eq!(config.max_gas_fee_lamports, DEFAULT_MAX_GAS_FEE_LAMPORTS);
+        assert_eq!(config.gas_reimbursement, fee_receiver);
+        assert_eq!(config.max_active_games_per_player, DEFAULT_MAX_ACTIVE_GAMES_PER_PLAYER);
+    }
+
+    #[test]
+    fn migrates_v1_config_without_touching_existing_fields() {
+        let admin = Pubkey::new_unique();
+        let config = GameConfig {
+            admin,
+            fee_receiver: Pubkey::new_unique(),
+            fee_bps: 50,
+            first_place_percentage: 50,
+            second_place_percentage: 30,
+            third_place_percentage: 20,
+            next_game_id: 7,
+            bump: 254,
+            version: 1,
+            settlement_authority: Pubkey::new_unique(),
+            pauser: admin,
+            treasury_authority: admin,
+            paused: PAUSE_JOIN,
+            config_timelock_secs: 60,
+            outstanding_liabilities: 500,
+            max_gas_fee_lamports: 10_000,
+            gas_reimbursement: Pubkey::new_unique(),
+            max_active_games_per_player: 0,
+            referral_share_bps: 0,
+            fee_tiers: [FeeTier::default(); MAX_FEE_TIERS],
+            blocklist_authority: Pubkey::default(),
+            balance_withdrawal_cooldown_secs: 0,
+            reserved: [0; CONFIG_RESERVED_BYTES],
+        };
+        let mut data = Vec::new();
+        config.try_serialize(&mut data).unwrap();
+        // Version 1 accounts stop before `max_active_games_per_player`
+        data.truncate(GameConfig::SIZE - 2 - 2 - MAX_FEE_TIERS * FeeTier::SIZE - 32 - 8 - CONFIG_RESERVED_BYTES);
+        let mut data = zero_extended(data);
+
+        assert_eq!(migrate_config_data(&mut data, 1_000).unwrap(), 1);
+
+        let migrated = GameConfig::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(migrated.version, CONFIG_VERSION);
+        assert_eq!(migrated.max_active_games_per_player, DEFAULT_MAX_ACTIVE_GAMES_PER_PLAYER);
+        assert_eq!(migrated.referral_share_bps, 0);
+        assert_eq!(migrated.fee_bps_for(u64::MAX), 50);
+        assert_eq!(migrated.blocklist_authority, admin);
+        assert_eq!(migrated.balance_withdrawal_cooldown_secs, 0);
+        assert_eq!(migrated.settlement_authority, config.settlement_authority);
+        assert_eq!(migrated.paused, PAUSE_JOIN);
+        assert_eq!(migrated.config_timelock_secs, 60);
+        // Liabilities were already tracked, so the vault balance is not re-applied
+        assert_eq!(migrated.outstanding_liabilities, 500);
+        assert_eq!(migrated.gas_reimbursement, config.gas_reimbursement);
+    }
+
+    #[test]
+    fn duplicate_places_are_rejected() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let placements = [Some(a), Some(b), None];
+
+        let paid = [WinnerPayout { player: a, place: 1 }, WinnerPayout { player: b, place: 2 }];
+        assert!(check_placements(&placements, &paid).is_ok());
+
+        let doubled = [WinnerPayout { player: a, place: 1 }, WinnerPayout { player: a, place: 1 }];
+        assert_eq!(
+            check_placements(&placements, &doubled).unwrap_err(),
+            GameError::PlacementMismatch.into()
+        );
+    }
+
+    fn serialized_len<T: AccountSerialize>(account: &T) -> usize {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        data.len()
+    }
+
+    #[test]
+    fn accounts_fit_the_space_of_their_game_type() {
+        let players = |n: usize| (0..n).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        let placed = [Some(Pubkey::new_unique()); PAID_PLACES];
+
+        for (game_type, entrants) in [
+            (GameType::Tournament { size: 2 }, 2),
+            (GameType::Tournament { size: 8 }, 5),
+            (GameType::Tournament { size: MAX_TOURNAMENT_SIZE as u8 }, MAX_TOURNAMENT_SIZE),
+            (GameType::DoubleElimination { size: 4, bracket_reset: true }, 4),
+            (GameType::DoubleElimination { size: MAX_TOURNAMENT_SIZE as u8, bracket_reset: true }, MAX_TOURNAMENT_SIZE),
+        ] {
+            let mut bracket = Bracket {
+                game_id: 1,
+                seed: [0; 32],
+                seed_slot: 0,
+                size: 0,
+                players: Vec::new(),
+                matches: Vec::new(),
+                placements: [None; PAID_PLACES],
+                byes: Vec::new(),
+                grand_final: NO_MATCH,
+                reset_match: NO_MATCH,
+                completed: false,
+                bump: 0,
+                rent_payer: Pubkey::new_unique(),
+            };
+            match game_type {
+                GameType::DoubleElimination { bracket_reset, .. } => {
+                    bracket.build_double_elimination(players(entrants), bracket_reset).unwrap()
+                }
+                _ => bracket.build_single_elimination(players(entrants)).unwrap(),
+            }
+            bracket.placements = placed;
+            assert!(serialized_len(&bracket) <= Bracket::space(&game_type));
+        }
+
+        for (game_type, entrants) in [
+            (GameType::Swiss { size: 5, rounds: 2 }, 5),
+            (GameType::Swiss { size: MAX_SWISS_SIZE as u8, rounds: 5 }, MAX_SWISS_SIZE),
+            (GameType::League { size: MAX_LEAGUE_SIZE as u8 }, MAX_LEAGUE_SIZE),
+        ] {
+            let mut standings = Standings {
+                game_id: 1,
+                seed: [0; 32],
+                seed_slot: 0,
+                round: 0,
+                total_rounds: 0,
+                entries: Vec::new(),
+                pairings: Vec::new(),
+                placements: [None; PAID_PLACES],
+                completed: false,
+                bump: 0,
+                rent_payer: Pubkey::new_unique(),
+            };
+            match game_type {
+                GameType::Swiss { rounds, .. } => standings.start_swiss(players(entrants), rounds).unwrap(),
+                _ => standings.start_league(players(entrants)).unwrap(),
+            }
+            standings.placements = placed;
+            assert!(serialized_len(&standings) <= Standings::space(&game_type));
+        }
+
+        let mut game = Game {
+            id: 1,
+            creator: Pubkey::new_unique(),
+            game_type: GameType::Swiss { size: MAX_SWISS_SIZE as u8, rounds: 5 },
+            tier: GameTier::PointOne,
+            stake_per_player: 1,
+            max_players: MAX_SWISS_SIZE as u8,
+            status: GameStatus::Completed,
+            players: Vec::new(),
+            total_pot: 0,
+            created_at: 0,
+            bump: 0,
+            gas_fees_deducted: 0,
+            reserved_for: Pubkey::default(),
+            previous_game_id: 0,
+            rematch_game_id: 0,
+            stake_terms: Vec::new(),
+            prizes: Vec::new(),
+            profiles_released: 0,
+        };
+        for player in players(MAX_GAME_PLAYERS) {
+            game.add_player(
+                PlayerStake { player, amount: 1, joined_at: 0 },
+                StakeTerms { fee_bps: 0, payer: player, from_balance: false },
+            );
+        }
+        game.prizes = (1..=PAID_PLACES as u8)
+            .map(|place| Prize { player: Pubkey::new_unique(), place, amount: 1 })
+            .collect();
+        assert!(serialized_len(&game) <= Game::space(MAX_GAME_PLAYERS));
+    }
+
+    #[test]
+    fn swiss_round_falls_back_to_rematches() {
+        let mut standings = Standings {
+            game_id: 1,
+            seed: [0; 32],
+            seed_slot: 0,
+            round: 0,
+            total_rounds: 0,
+            entries: Vec::new(),
+            pairings: Vec::new(),
+            placements: [None; PAID_PLACES],
+            completed: false,
+            bump: 0,
+            rent_payer: Pubkey::new_unique(),
+        };
+        standings.start_swiss((0..4).map(|_| Pubkey::new_unique()).collect(), 2).unwrap();
+
+        // Everyone has already met everyone, so only rematches are left
+        for entry in standings.entries.iter_mut() {
+            entry.opponents = 0b1111;
+        }
+        standings.pair_swiss_round().unwrap();
+
+        let paired: Vec<(u8, u8)> = standings.pairings.iter().map(|p| (p.a, p.b)).collect();
+        assert_eq!(paired.len(), 2);
+        assert!(paired.iter().all(|&(a, b)| a != b && b != NO_PLAYER));
+    }
+
+    /// Seats every game account was allocated for by the original program
+    const LEGACY_SEATS: usize = 16;
+
+    /// Size of those accounts: the fields up to `bump` plus 500 spare bytes (1350 in all)
+    const LEGACY_GAME_SIZE: usize =
+        8 + 8 + 32 + 2 + 1 + 8 + 1 + 1 + (4 + LEGACY_SEATS * (32 + 8 + 8)) + 8 + 8 + 1 + 500;
+
+    /// Game bytes as written by the original program, which stopped at `bump`
+    fn legacy_game(players: &[Pubkey]) -> Vec<u8> {
+        let mut data = Game::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&3u64.to_le_bytes());
+        data.extend_from_slice(players[0].as_ref());
+        data.extend_from_slice(&[1, LEGACY_SEATS as u8]); // Tournament { size }
+        data.push(0); // GameTier::PointOne
+        data.extend_from_slice(&100_000_000u64.to_le_bytes());
+        data.push(LEGACY_SEATS as u8);
+        data.push(0); // GameStatus::WaitingForPlayers
+        data.extend_from_slice(&(players.len() as u32).to_le_bytes());
+        for player in players {
+            data.extend_from_slice(player.as_ref());
+            data.extend_from_slice(&100_000_000u64.to_le_bytes());
+            data.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        }
+        data.extend_from_slice(&(players.len() as u64 * 100_000_000).to_le_bytes());
+        data.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        data.push(255);
+        data.resize(LEGACY_GAME_SIZE, 0);
+        data
+    }
+
+    #[test]
+    fn legacy_game_reads_with_default_terms() {
+        let players: Vec<Pubkey> = (1..LEGACY_SEATS).map(|_| Pubkey::new_unique()).collect();
+        let mut game = Game::try_deserialize(&mut &legacy_game(&players)[..]).unwrap();
+
+        assert_eq!(game.players.len(), LEGACY_SEATS - 1);
+        assert_eq!(game.total_pot, (LEGACY_SEATS as u64 - 1) * 100_000_000);
+        assert_eq!(game.gas_fees_deducted, 0);
+        assert!(game.stake_terms.is_empty());
+        assert_eq!(game.rematch_game_id, 0);
+        assert_eq!(game.terms(1).payer, players[1]);
+        assert_eq!(game.payout_account(&players[2]), players[2]);
+
+        // The next entry fills in terms for everyone before it
+        let (joiner, balance) = (Pubkey::new_unique(), Pubkey::new_unique());
+        game.add_player(
+            PlayerStake { player: joiner, amount: 100_000_000, joined_at: 0 },
+            StakeTerms { fee_bps: 50, payer: balance, from_balance: true },
+        );
+        assert_eq!(game.stake_terms.len(), LEGACY_SEATS);
+        assert_eq!(game.payout_account(&joiner), balance);
+
+        let (stake, terms) = game.remove_player(0);
+        assert_eq!(stake.player, players[0]);
+        assert_eq!(terms.payer, players[0]);
+        assert_eq!(game.terms(LEGACY_SEATS - 2).payer, balance);
+
+        // A full game fits the account `join_game` grows it to
+        game.add_player(
+            PlayerStake { player: players[0], amount: 100_000_000, joined_at: 0 },
+            StakeTerms { fee_bps: 50, payer: balance, from_balance: false },
+        );
+        let mut account = vec![0; Game::space(LEGACY_SEATS)];
+        game.try_serialize(&mut &mut account[..]).unwrap();
+    }
+
+    #[test]
+    fn settled_legacy_game_fits_its_original_account() {
+        // Only `join_game` grows a game, so a legacy game that was already full is withdrawn
+        // from, cancelled, paid out and released in the account it was created with
+        let players: Vec<Pubkey> = (0..LEGACY_SEATS).map(|_| Pubkey::new_unique()).collect();
+        let mut account = legacy_game(&players);
+        let mut game = Game::try_deserialize(&mut &account[..]).unwrap();
+
+        game.status = GameStatus::Completed;
+        game.gas_fees_deducted = 5_000;
+        game.rematch_game_id = 9;
+        game.prizes = players
+            .iter()
+            .zip(1..=PAID_PLACES as u8)
+            .map(|(player, place)| Prize { player: *player, place, amount: 100_000_000 })
+            .collect();
+        game.profiles_released = (1 << LEGACY_SEATS) - 1;
+        game.try_serialize(&mut &mut account[..]).unwrap();
+        assert_eq!(account.len(), LEGACY_GAME_SIZE);
+
+        let settled = Game::try_deserialize(&mut &account[..]).unwrap();
+        assert_eq!(settled.players.len(), LEGACY_SEATS);
+        assert!(settled.stake_terms.is_empty());
+        assert_eq!(settled.prizes.len(), PAID_PLACES);
+        assert_eq!(settled.gas_fees_deducted, 5_000);
+        assert_eq!(settled.rematch_game_id, 9);
+    }
+
+    #[test]
+    fn rematch_reservation_expires() {
+        let creator = Pubkey::new_unique();
+        let (opponent, stranger) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let game = Game {
+            id: 2,
+            creator,
+            game_type: GameType::OneVsOne,
+            tier: GameTier::PointOne,
+            stake_per_player: 100_000_000,
+            max_players: 2,
+            status: GameStatus::WaitingForPlayers,
+            players: vec![PlayerStake { player: creator, amount: 100_000_000, joined_at: 1_000 }],
+            total_pot: 100_000_000,
+            created_at: 1_000,
+            bump: 255,
+            gas_fees_deducted: 0,
+            reserved_for: opponent,
+            previous_game_id: 1,
+            rematch_game_id: 0,
+            stake_terms: vec![StakeTerms { fee_bps: 50, payer: creator, from_balance: false }],
+            prizes: Vec::new(),
+            profiles_released: 0,
+        };
+
+        assert!(game.seat_open_to(&opponent, 1_000));
+        assert!(!game.seat_open_to(&stranger, 1_000 + REMATCH_RESERVATION_SECS - 1));
+        assert!(game.seat_open_to(&stranger, 1_000 + REMATCH_RESERVATION_SECS));
+    }
+
+    #[test]
+    fn balance_cooldown_ends_at_its_last_second() {
+        let balance = PlayerBalance {
+            player: Pubkey::new_unique(),
+            last_deposit_at: 1_000,
+            bump: 255,
+        };
+
+        assert_eq!(
+            balance.check_cooldown(3_600, 4_599).unwrap_err(),
+            GameError::BalanceCooldownActive.into()
+        );
+        assert!(balance.check_cooldown(3_600, 4_600).is_ok());
+        assert!(balance.check_cooldown(0, 1_000).is_ok());
+    }
+
+    #[test]
+    fn session_key_is_limited_to_its_games_and_lifetime() {
+        let player = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let session = SessionKey {
+            player,
+            session_key: key,
+            game_ids: [3, 9, 0, 0],
+            expires_at: 1_000,
+            bump: 255,
+        };
+        let check = |session: Option<&SessionKey>, signer: &Pubkey, game_id: u64, now: i64| {
+            SessionKey::check_session(session, &player, signer, game_id, now)
+        };
+
+        assert!(check(Some(&session), &key, 9, 999).is_ok());
+        // The wallet itself never needs a session
+        assert!(check(None, &player, 5, 2_000).is_ok());
+
+        for (session, signer, game_id, now) in [
+            (Some(&session), &key, 9, 1_000),
+            (Some(&session), &key, 5, 999),
+            (Some(&session), &key, 0, 999),
+            (Some(&session), &Pubkey::new_unique(), 9, 999),
+            (None, &key, 9, 999),
+        ] {
+            assert_eq!(
+                check(session, signer, game_id, now).unwrap_err(),
+                GameError::SessionNotAuthorized.into()
+            );
+        }
+    }
+
+    #[test]
+    fn legacy_layout_does_not_deserialize_without_migration() {
+        let data = legacy_config(Pubkey::new_unique(), Pubkey::new_unique());
+        assert!(GameConfig::try_deserialize(&mut &data[..]).is_err());
+    }
+
+    #[test]
+    fn current_config_is_not_migrated_twice() {
+        let mut data = zero_extended(legacy_config(Pubkey::new_unique(), Pubkey::new_unique()));
+        migrate_config_data(&mut data, 0).unwrap();
+        let migrated = data.clone();
+
+        assert_eq!(
+            migrate_config_data(&mut data, 0).unwrap_err(),
+            GameError::ConfigAlreadyCurrent.into()
+        );
+        assert_eq!(data, migrated);
+    }
+}
@@ -1,7 +1,8 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { GamingVault } from "../target/types/gaming_vault";
-import { PublicKey, Keypair, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import { PublicKey, Keypair, LAMPORTS_PER_SOL, SYSVAR_SLOT_HASHES_PUBKEY } from "@solana/web3.js";
+import { createHash } from "crypto";
 import { expect } from "chai";
 
 describe("gaming_vault", () => {
@@ -21,15 +22,170 @@ describe("gaming_vault", () => {
   // PDAs
   let gameConfigPda: PublicKey;
   let vaultPda: PublicKey;
+  let programDataPda: PublicKey;
+  let adminActionLogPda: PublicKey;
+
+  // Short timelock so the suite can execute a queued config change
+  const CONFIG_TIMELOCK_SECS = 1;
 
   // Game PDAs - will be set dynamically
   let game1Pda: PublicKey;
   let game2Pda: PublicKey;
   let game3Pda: PublicKey;
+  let bracket2Pda: PublicKey;
+
+  const profilePda = (wallet: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("player_profile"), wallet.toBuffer()],
+      program.programId
+    )[0];
+
+  const blockedPda = (wallet: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("blocked"), wallet.toBuffer()],
+      program.programId
+    )[0];
+
+  const balancePda = (player: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("player_balance"), player.toBuffer()],
+      program.programId
+    )[0];
+
+  // Settlement takes each winner's payout account
+  const settlementAccounts = async (gamePda: PublicKey, winners: PublicKey[]) => {
+    const game = await program.account.game.fetch(gamePda);
+    return winners.map((winner) => {
+      // Winnings of entries paid from an internal balance go back to that balance
+      const index = game.players.findIndex((p) => p.player.equals(winner));
+      const terms = game.stakeTerms[index];
+      const pubkey = terms?.fromBalance ? terms.payer : winner;
+      return { pubkey, isWritable: true, isSigner: false };
+    });
+  };
+
+  // Cancellation takes every stake's payer, in `game.players` order. Games from before
+  // `stakeTerms` existed have none for their earlier players, who paid for themselves.
+  const refundAccounts = async (gamePda: PublicKey) => {
+    const game = await program.account.game.fetch(gamePda);
+    return game.players.map((p, i) => ({
+      pubkey: game.stakeTerms[i]?.payer ?? p.player,
+      isWritable: true,
+      isSigner: false,
+    }));
+  };
+
+  // Frees every player's slot in a settled game and records the result on their profile
+  const releaseActiveGames = async (gamePda: PublicKey) => {
+    const game = await program.account.game.fetch(gamePda);
+    for (const [index, p] of game.players.entries()) {
+      if (game.profilesReleased & (1 << index)) continue;
+      await program.methods
+        .releaseActiveGame(game.id, p.player)
+        .accounts({
+          game: gamePda,
+          playerProfile: profilePda(p.player),
+        })
+        .rpc();
+    }
+  };
+
+  // Opens a 0.1 SOL 1v1 for `creator`, with `payer` funding the entry
+  const createOneVsOne = async (
+    creator: Keypair,
+    payer: Keypair = creator,
+    { referrer = null, playerBalance = null }: { referrer?: PublicKey; playerBalance?: PublicKey } = {}
+  ) => {
+    const gameId = (await program.account.gameConfig.fetch(gameConfigPda)).nextGameId;
+    const [gamePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("game"), gameId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    await program.methods
+      .createGame({ oneVsOne: {} }, { pointOne: {} }, referrer)
+      .accounts({
+        game: gamePda,
+        bracket: null,
+        standings: null,
+        gameConfig: gameConfigPda,
+        creator: creator.publicKey,
+        payer: payer.publicKey,
+        creatorProfile: profilePda(creator.publicKey),
+        blocked: blockedPda(creator.publicKey),
+        referrerProfile: referrer && profilePda(referrer),
+        playerBalance,
+        vault: vaultPda,
+        feeReceiver: feeReceiverKeypair.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers(payer === creator ? [creator] : [creator, payer])
+      .rpc();
+    return { gameId, gamePda };
+  };
+
+  const [pendingChangePda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("pending_config_change")],
+    program.programId
+  );
+
+  type ConfigChange = {
+    feeBps?: number;
+    prizePercentages?: number[];
+    maxActiveGamesPerPlayer?: number;
+    referralShareBps?: number;
+    feeTiers?: { minVolumeLamports: anchor.BN; feeBps: number }[];
+    balanceWithdrawalCooldownSecs?: number;
+  };
+
+  const queueConfigChange = (change: ConfigChange) =>
+    program.methods
+      .queueConfigChange(
+        change.feeBps ?? null,
+        change.prizePercentages ?? null,
+        null,
+        null,
+        null,
+        null,
+        change.maxActiveGamesPerPlayer ?? null,
+        change.referralShareBps ?? null,
+        change.feeTiers ?? null,
+        change.balanceWithdrawalCooldownSecs === undefined
+          ? null
+          : new anchor.BN(change.balanceWithdrawalCooldownSecs)
+      )
+      .accounts({
+        pendingChange: pendingChangePda,
+        gameConfig: gameConfigPda,
+        adminActionLog: adminActionLogPda,
+        admin: adminKeypair.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([adminKeypair])
+      .rpc();
+
+  const executeConfigChange = () =>
+    program.methods
+      .executeConfigChange()
+      .accounts({
+        pendingChange: pendingChangePda,
+        gameConfig: gameConfigPda,
+        adminActionLog: adminActionLogPda,
+        admin: adminKeypair.publicKey,
+        proposer: adminKeypair.publicKey,
+      })
+      .signers([adminKeypair])
+      .rpc();
+
+  // Queues a config change, waits out the timelock and executes it
+  const applyConfigChange = async (change: ConfigChange) => {
+    await queueConfigChange(change);
+    await new Promise(resolve => setTimeout(resolve, (CONFIG_TIMELOCK_SECS + 2) * 1000));
+    await executeConfigChange();
+  };
 
   before(async () => {
-    // Create test keypairs
-    adminKeypair = Keypair.generate();
+    // The deploying wallet is the program's upgrade authority and therefore the only valid admin
+    adminKeypair = (provider.wallet as anchor.Wallet).payer;
     feeReceiverKeypair = Keypair.generate();
     player1Keypair = Keypair.generate();
     player2Keypair = Keypair.generate();
@@ -63,23 +219,59 @@ describe("gaming_vault", () => {
       program.programId
     );
 
+    [adminActionLogPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("admin_action_log")],
+      program.programId
+    );
+
+    [programDataPda] = PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+
     console.log("Game config PDA:", gameConfigPda.toString());
     console.log("Vault PDA:", vaultPda.toString());
   });
 
   describe("Contract Initialization", () => {
+    it("Should reject initialization from a non-upgrade-authority", async () => {
+      try {
+        await program.methods
+          .initialize(50, 50, 30, 20, new anchor.BN(CONFIG_TIMELOCK_SECS))
+          .accounts({
+            gameConfig: gameConfigPda,
+            adminActionLog: adminActionLogPda,
+            admin: player1Keypair.publicKey,
+            feeReceiver: player1Keypair.publicKey,
+            program: program.programId,
+            programData: programDataPda,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([player1Keypair])
+          .rpc();
+        expect.fail("Should have failed with UnauthorizedAccess");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAccess");
+        console.log("✅ Correctly rejected front-run initialization");
+      }
+    });
+
     it("Initialize contract configuration", async () => {
       const tx = await program.methods
         .initialize(
           50, // 0.5% fee (50 basis points)
           50, // 50% first place
           30, // 30% second place
-          20  // 20% third place
+          20, // 20% third place
+          new anchor.BN(CONFIG_TIMELOCK_SECS)
         )
         .accounts({
           gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
           admin: adminKeypair.publicKey,
           feeReceiver: feeReceiverKeypair.publicKey,
+          program: program.programId,
+          programData: programDataPda,
           systemProgram: anchor.web3.SystemProgram.programId,
         })
         .signers([adminKeypair])
@@ -96,6 +288,31 @@ describe("gaming_vault", () => {
       expect(config.secondPlacePercentage).to.equal(30);
       expect(config.thirdPlacePercentage).to.equal(20);
       expect(config.nextGameId.toString()).to.equal("1");
+      expect(config.version).to.equal(7); // CONFIG_VERSION
+
+      // The admin action log is created alongside the config
+      const log = await program.account.adminActionLog.fetch(adminActionLogPda);
+      expect(log.entries).to.have.length(0);
+      expect(log.totalActions.toString()).to.equal("0");
+    });
+
+    it("Migrating a current config is rejected", async () => {
+      try {
+        await program.methods
+          .migrateConfig()
+          .accounts({
+            gameConfig: gameConfigPda,
+            adminActionLog: adminActionLogPda,
+            admin: adminKeypair.publicKey,
+            vault: vaultPda,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([adminKeypair])
+          .rpc();
+        expect.fail("Should have failed with ConfigAlreadyCurrent");
+      } catch (error) {
+        expect(error.toString()).to.include("ConfigAlreadyCurrent");
+      }
     });
   });
 
@@ -120,12 +337,20 @@ describe("gaming_vault", () => {
       const tx = await program.methods
         .createGame(
           { oneVsOne: {} },
-          { pointOne: {} }
+          { pointOne: {} },
+          null
         )
         .accounts({
           game: game1Pda,
+          bracket: null,
+          standings: null,
           gameConfig: gameConfigPda,
           creator: player1Keypair.publicKey,
+          payer: player1Keypair.publicKey,
+          creatorProfile: profilePda(player1Keypair.publicKey),
+          blocked: blockedPda(player1Keypair.publicKey),
+          referrerProfile: null,
+          playerBalance: null,
           vault: vaultPda,
           feeReceiver: feeReceiverKeypair.publicKey,
           systemProgram: anchor.web3.SystemProgram.programId,
@@ -155,6 +380,13 @@ describe("gaming_vault", () => {
       expect(player1BalanceBefore - player1BalanceAfter).to.be.greaterThan(stakeAmount + feeAmount);
       expect(vaultBalanceAfter - vaultBalanceBefore).to.equal(stakeAmount);
       expect(feeReceiverBalanceAfter - feeReceiverBalanceBefore).to.equal(feeAmount);
+
+      // The creator's profile is created lazily; nothing is settled yet
+      const profile = await program.account.playerProfile.fetch(profilePda(player1Keypair.publicKey));
+      expect(profile.player.toString()).to.equal(player1Keypair.publicKey.toString());
+      expect(profile.gamesPlayed).to.equal(0);
+      expect(profile.activeGames).to.equal(1);
+      expect(profile.lastActiveSlot.toNumber()).to.be.greaterThan(0);
     });
 
     it("Create a tournament game (0.5 SOL tier)", async () => {
@@ -167,18 +399,31 @@ describe("gaming_vault", () => {
         program.programId
       );
 
+      [bracket2Pda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("bracket"), gameId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
       console.log("Creating tournament with ID:", gameId.toString());
       console.log("Game2 PDA:", game2Pda.toString());
 
       const tx = await program.methods
         .createGame(
           { tournament: { size: 4 } },
-          { pointFive: {} }
+          { pointFive: {} },
+          null
         )
         .accounts({
           game: game2Pda,
+          bracket: bracket2Pda,
+          standings: null,
           gameConfig: gameConfigPda,
           creator: player2Keypair.publicKey,
+          payer: player2Keypair.publicKey,
+          creatorProfile: profilePda(player2Keypair.publicKey),
+          blocked: blockedPda(player2Keypair.publicKey),
+          referrerProfile: null,
+          playerBalance: null,
           vault: vaultPda,
           feeReceiver: feeReceiverKeypair.publicKey,
           systemProgram: anchor.web3.SystemProgram.programId,
@@ -197,6 +442,39 @@ describe("gaming_vault", () => {
       expect(game.players).to.have.length(1);
       expect(game.status).to.deep.equal({ waitingForPlayers: {} });
     });
+
+    it("Fees cannot be sent to anyone but the configured fee receiver", async () => {
+      const config = await program.account.gameConfig.fetch(gameConfigPda);
+      const [gamePda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("game"), config.nextGameId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .createGame({ oneVsOne: {} }, { pointOne: {} }, null)
+          .accounts({
+            game: gamePda,
+            bracket: null,
+            standings: null,
+            gameConfig: gameConfigPda,
+            creator: player3Keypair.publicKey,
+            payer: player3Keypair.publicKey,
+            creatorProfile: profilePda(player3Keypair.publicKey),
+            blocked: blockedPda(player3Keypair.publicKey),
+            referrerProfile: null,
+            playerBalance: null,
+            vault: vaultPda,
+            feeReceiver: player3Keypair.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([player3Keypair])
+          .rpc();
+        expect.fail("Should have failed with FeeReceiverMismatch");
+      } catch (error) {
+        expect(error.toString()).to.include("FeeReceiverMismatch");
+      }
+    });
   });
 
   describe("Joining Games", () => {
@@ -208,13 +486,21 @@ describe("gaming_vault", () => {
       console.log("Joining game with ID:", gameId.toString());
 
       const tx = await program.methods
-        .joinGame(gameId)
+        .joinGame(gameId, null)
         .accounts({
           game: game1Pda,
+          bracket: null,
+          standings: null,
           gameConfig: gameConfigPda,
           player: player2Keypair.publicKey,
+          payer: player2Keypair.publicKey,
+          playerProfile: profilePda(player2Keypair.publicKey),
+          blocked: blockedPda(player2Keypair.publicKey),
+          referrerProfile: null,
+          playerBalance: null,
           vault: vaultPda,
           feeReceiver: feeReceiverKeypair.publicKey,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
           systemProgram: anchor.web3.SystemProgram.programId,
         })
         .signers([player2Keypair])
@@ -237,13 +523,21 @@ describe("gaming_vault", () => {
 
       // Player 1 joins tournament
       await program.methods
-        .joinGame(gameId)
+        .joinGame(gameId, null)
         .accounts({
           game: game2Pda,
+          bracket: bracket2Pda,
+          standings: null,
           gameConfig: gameConfigPda,
           player: player1Keypair.publicKey,
+          payer: player1Keypair.publicKey,
+          playerProfile: profilePda(player1Keypair.publicKey),
+          blocked: blockedPda(player1Keypair.publicKey),
+          referrerProfile: null,
+          playerBalance: null,
           vault: vaultPda,
           feeReceiver: feeReceiverKeypair.publicKey,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
           systemProgram: anchor.web3.SystemProgram.programId,
         })
         .signers([player1Keypair])
@@ -256,13 +550,21 @@ describe("gaming_vault", () => {
 
       // Player 3 joins
       await program.methods
-        .joinGame(gameId)
+        .joinGame(gameId, null)
         .accounts({
           game: game2Pda,
+          bracket: bracket2Pda,
+          standings: null,
           gameConfig: gameConfigPda,
           player: player3Keypair.publicKey,
+          payer: player3Keypair.publicKey,
+          playerProfile: profilePda(player3Keypair.publicKey),
+          blocked: blockedPda(player3Keypair.publicKey),
+          referrerProfile: null,
+          playerBalance: null,
           vault: vaultPda,
           feeReceiver: feeReceiverKeypair.publicKey,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
           systemProgram: anchor.web3.SystemProgram.programId,
         })
         .signers([player3Keypair])
@@ -283,13 +585,21 @@ describe("gaming_vault", () => {
 
       // Player 4 joins to complete the tournament
       const tx = await program.methods
-        .joinGame(gameId)
+        .joinGame(gameId, null)
         .accounts({
           game: game2Pda,
+          bracket: bracket2Pda,
+          standings: null,
           gameConfig: gameConfigPda,
           player: player4Keypair.publicKey,
+          payer: player4Keypair.publicKey,
+          playerProfile: profilePda(player4Keypair.publicKey),
+          blocked: blockedPda(player4Keypair.publicKey),
+          referrerProfile: null,
+          playerBalance: null,
           vault: vaultPda,
           feeReceiver: feeReceiverKeypair.publicKey,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
           systemProgram: anchor.web3.SystemProgram.programId,
         })
         .signers([player4Keypair])
@@ -311,13 +621,21 @@ describe("gaming_vault", () => {
 
       try {
         await program.methods
-          .joinGame(gameId)
+          .joinGame(gameId, null)
           .accounts({
             game: game2Pda,
+            bracket: bracket2Pda,
+            standings: null,
             gameConfig: gameConfigPda,
-            player: player1Keypair.publicKey, // Already in this game
+            player: player1Keypair.publicKey,
+            payer: player1Keypair.publicKey,
+            playerProfile: profilePda(player1Keypair.publicKey), // Already in this game
+            blocked: blockedPda(player1Keypair.publicKey),
+            referrerProfile: null,
+            playerBalance: null,
             vault: vaultPda,
             feeReceiver: feeReceiverKeypair.publicKey,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
             systemProgram: anchor.web3.SystemProgram.programId,
           })
           .signers([player1Keypair])
@@ -331,214 +649,2397 @@ describe("gaming_vault", () => {
     });
   });
 
-  describe("Admin Functions", () => {
-    it("Create game for admin tests", async () => {
-      // Create a game for admin function testing
-      const config = await program.account.gameConfig.fetch(gameConfigPda);
-      const gameId = config.nextGameId;
+  describe("Tournament Bracket", () => {
+    it("Bracket is seeded when the tournament starts", async () => {
+      const bracket = await program.account.bracket.fetch(bracket2Pda);
+      const game = await program.account.game.fetch(game2Pda);
 
-      [game3Pda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("game"), gameId.toArrayLike(Buffer, "le", 8)],
-        program.programId
-      );
+      expect(bracket.size).to.equal(4);
+      expect(bracket.completed).to.equal(false);
+      // Two semifinals, the final and the third-place match
+      expect(bracket.matches).to.have.length(4);
+      expect(bracket.matches[0].status).to.deep.equal({ ready: {} });
+      expect(bracket.matches[1].status).to.deep.equal({ ready: {} });
+      expect(bracket.matches[2].status).to.deep.equal({ pending: {} });
+      expect(bracket.matches[3].status).to.deep.equal({ pending: {} });
+
+      const seeded = bracket.matches
+        .slice(0, 2)
+        .flatMap((m) => m.players.map((p) => p.toString()))
+        .sort();
+      const joined = game.players.map((p) => p.player.toString()).sort();
+      expect(seeded).to.deep.equal(joined);
+    });
 
-      console.log("Creating admin test game with ID:", gameId.toString());
-      console.log("Game3 PDA:", game3Pda.toString());
+    it("Anyone can recompute the seeding from the recorded seed", async () => {
+      const bracket = await program.account.bracket.fetch(bracket2Pda);
+      const game = await program.account.game.fetch(game2Pda);
 
-      await program.methods
-        .createGame(
-          { oneVsOne: {} },
-          { pointOne: {} }
-        )
-        .accounts({
-          game: game3Pda,
-          gameConfig: gameConfigPda,
-          creator: player3Keypair.publicKey,
-          vault: vaultPda,
-          feeReceiver: feeReceiverKeypair.publicKey,
-          systemProgram: anchor.web3.SystemProgram.programId,
-        })
-        .signers([player3Keypair])
-        .rpc();
+      expect(bracket.seedSlot.toNumber()).to.be.greaterThan(0);
+
+      // Fisher-Yates with sha256(seed || i) as the source of randomness
+      const seed = Buffer.from(bracket.seed);
+      const players = game.players.map((p) => p.player);
+      for (let i = players.length - 1; i > 0; i--) {
+        const index = Buffer.alloc(8);
+        index.writeBigUInt64LE(BigInt(i));
+        const digest = createHash("sha256").update(seed).update(index).digest();
+        const j = Number(digest.readBigUInt64LE(0) % BigInt(i + 1));
+        [players[i], players[j]] = [players[j], players[i]];
+      }
 
-      console.log("✅ Created game for admin tests");
+      expect(bracket.players.map((p) => p.toString())).to.deep.equal(
+        players.map((p) => p.toString())
+      );
     });
 
-    it("Admin can withdraw player before game starts", async () => {
-      // Get the actual game ID from the game state
-      const game3 = await program.account.game.fetch(game3Pda);
-      const gameId = game3.id;
+    it("Cannot pay out a tournament before the bracket completes", async () => {
+      const game2 = await program.account.game.fetch(game2Pda);
 
-      const player3BalanceBefore = await provider.connection.getBalance(player3Keypair.publicKey);
-      const vaultBalanceBefore = await provider.connection.getBalance(vaultPda);
+      try {
+        await program.methods
+          .adminPayoutWinners(
+            game2.id,
+            [{ player: player1Keypair.publicKey, place: 1 }],
+            new anchor.BN(0)
+          )
+          .accounts({
+            game: game2Pda,
+            bracket: bracket2Pda,
+            standings: null,
+            gameConfig: gameConfigPda,
+            adminActionLog: adminActionLogPda,
+            admin: adminKeypair.publicKey,
+            vault: vaultPda,
+            gasReimbursement: feeReceiverKeypair.publicKey,
+            rentPayer: player2Keypair.publicKey,
+          })
+          .remainingAccounts(await settlementAccounts(game2Pda, [player1Keypair.publicKey]))
+          .signers([adminKeypair])
+          .rpc();
 
-      const gasFee = 5000;
-      const tx = await program.methods
-        .adminWithdrawPlayer(
-          gameId,
-          player3Keypair.publicKey,
-          new anchor.BN(gasFee)
-        )
-        .accounts({
-          game: game3Pda,
-          gameConfig: gameConfigPda,
-          admin: adminKeypair.publicKey,
-          vault: vaultPda,
-        })
-        .remainingAccounts([
-          {
-            pubkey: player3Keypair.publicKey,
-            isWritable: true,
-            isSigner: false,
-          }
-        ])
-        .signers([adminKeypair])
-        .rpc();
+        expect.fail("Should have failed with BracketNotCompleted");
+      } catch (error) {
+        expect(error.toString()).to.include("BracketNotCompleted");
+        console.log("✅ Correctly rejected payout of unfinished bracket");
+      }
+    });
 
-      console.log("✅ Admin withdrew player:", tx);
+    it("Admin advances winners and pays out bracket placements", async () => {
+      const game2 = await program.account.game.fetch(game2Pda);
+      const gameId = game2.id;
 
-      // Verify game state
-      const game = await program.account.game.fetch(game3Pda);
-      expect(game.players).to.have.length(0);
-      expect(game.totalPot.toString()).to.equal("0");
+      const advance = async (matchIndex: number, winner: PublicKey) =>
+        program.methods
+          .advanceWinner(gameId, matchIndex, winner)
+          .accounts({
+            game: game2Pda,
+            bracket: bracket2Pda,
+            gameConfig: gameConfigPda,
+            adminActionLog: adminActionLogPda,
+            admin: adminKeypair.publicKey,
+          })
+          .signers([adminKeypair])
+          .rpc();
 
-      // Verify balance changes
-      const player3BalanceAfter = await provider.connection.getBalance(player3Keypair.publicKey);
-      const vaultBalanceAfter = await provider.connection.getBalance(vaultPda);
+      // Slot 0 wins every match
+      for (let i = 0; i < 4; i++) {
+        const bracket = await program.account.bracket.fetch(bracket2Pda);
+        await advance(i, bracket.matches[i].players[0]);
+      }
 
-      const stakeAmount = 0.1 * LAMPORTS_PER_SOL;
-      const refundAmount = stakeAmount - gasFee;
+      const bracket = await program.account.bracket.fetch(bracket2Pda);
+      expect(bracket.completed).to.equal(true);
 
-      expect(player3BalanceAfter - player3BalanceBefore).to.equal(refundAmount);
-      expect(vaultBalanceBefore - vaultBalanceAfter).to.equal(refundAmount);
-    });
+      const [first, second, third] = bracket.placements;
+      expect(first.toString()).to.equal(bracket.matches[2].players[0].toString());
+      expect(second.toString()).to.equal(bracket.matches[2].players[1].toString());
+      expect(third.toString()).to.equal(bracket.matches[3].players[0].toString());
 
-    it("Admin can payout winners (1v1 game)", async () => {
-      // Get the actual game ID from the game state
-      const game1 = await program.account.game.fetch(game1Pda);
-      const gameId = game1.id;
+      // Placements that disagree with the bracket are rejected
+      try {
+        await program.methods
+          .adminPayoutWinners(
+            gameId,
+            [
+              { player: second, place: 1 },
+              { player: first, place: 2 },
+              { player: third, place: 3 },
+            ],
+            new anchor.BN(0)
+          )
+          .accounts({
+            game: game2Pda,
+            bracket: bracket2Pda,
+            standings: null,
+            gameConfig: gameConfigPda,
+            adminActionLog: adminActionLogPda,
+            admin: adminKeypair.publicKey,
+            vault: vaultPda,
+            gasReimbursement: feeReceiverKeypair.publicKey,
+            rentPayer: player2Keypair.publicKey,
+          })
+          .remainingAccounts(await settlementAccounts(game2Pda, [second, first, third]))
+          .signers([adminKeypair])
+          .rpc();
 
-      const winnerBalanceBefore = await provider.connection.getBalance(player1Keypair.publicKey);
-      const vaultBalanceBefore = await provider.connection.getBalance(vaultPda);
+        expect.fail("Should have failed with PlacementMismatch");
+      } catch (error) {
+        expect(error.toString()).to.include("PlacementMismatch");
+      }
 
-      const gasFeePerWinner = 5000;
-      const winners = [
-        { player: player1Keypair.publicKey, place: 1 }
-      ];
+      const firstBalanceBefore = await provider.connection.getBalance(first);
+      const bracketRent = await provider.connection.getBalance(bracket2Pda);
 
-      const tx = await program.methods
+      await program.methods
         .adminPayoutWinners(
           gameId,
-          winners,
-          new anchor.BN(gasFeePerWinner)
+          [
+            { player: first, place: 1 },
+            { player: second, place: 2 },
+            { player: third, place: 3 },
+          ],
+          new anchor.BN(0)
         )
         .accounts({
-          game: game1Pda,
+          game: game2Pda,
+          bracket: bracket2Pda,
+          standings: null,
           gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
           admin: adminKeypair.publicKey,
           vault: vaultPda,
+          gasReimbursement: feeReceiverKeypair.publicKey,
+          rentPayer: player2Keypair.publicKey,
         })
-        .remainingAccounts([
-          {
-            pubkey: player1Keypair.publicKey,
-            isWritable: true,
-            isSigner: false,
-          }
-        ])
+        .remainingAccounts(await settlementAccounts(game2Pda, [first, second, third]))
         .signers([adminKeypair])
         .rpc();
+      await releaseActiveGames(game2Pda);
 
-      console.log("✅ Admin paid out 1v1 winner:", tx);
-
-      // Verify game completion
-      const game = await program.account.game.fetch(game1Pda);
+      const game = await program.account.game.fetch(game2Pda);
       expect(game.status).to.deep.equal({ completed: {} });
 
-      // Verify payout
-      const winnerBalanceAfter = await provider.connection.getBalance(player1Keypair.publicKey);
-      const vaultBalanceAfter = await provider.connection.getBalance(vaultPda);
+      // The bracket is closed and its rent goes back to whoever paid for it
+      expect(await provider.connection.getAccountInfo(bracket2Pda)).to.be.null;
 
-      const totalPot = 0.2 * LAMPORTS_PER_SOL;
-      const payout = totalPot - gasFeePerWinner;
+      const firstBalanceAfter = await provider.connection.getBalance(first);
+      const refundedRent = first.equals(player2Keypair.publicKey) ? bracketRent : 0;
+      expect(firstBalanceAfter - firstBalanceBefore).to.equal(1.0 * LAMPORTS_PER_SOL + refundedRent);
 
-      expect(winnerBalanceAfter - winnerBalanceBefore).to.equal(payout);
-      expect(vaultBalanceBefore - vaultBalanceAfter).to.equal(payout);
+      console.log("✅ Tournament paid out from bracket placements");
     });
 
-    it("Non-admin cannot call admin functions", async () => {
-      // Get the actual game ID from the game state
-      const game2 = await program.account.game.fetch(game2Pda);
-      const gameId = game2.id;
+    it("Admin can start an under-filled tournament with byes", async () => {
+      const config = await program.account.gameConfig.fetch(gameConfigPda);
+      const gameId = config.nextGameId;
 
-      try {
+      const [gamePda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("game"), gameId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [bracketPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("bracket"), gameId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      await program.methods
+        .createGame({ tournament: { size: 4 } }, { pointOne: {} }, null)
+        .accounts({
+          game: gamePda,
+          bracket: bracketPda,
+          standings: null,
+          gameConfig: gameConfigPda,
+          creator: player1Keypair.publicKey,
+          payer: player1Keypair.publicKey,
+          creatorProfile: profilePda(player1Keypair.publicKey),
+          blocked: blockedPda(player1Keypair.publicKey),
+          referrerProfile: null,
+          playerBalance: null,
+          vault: vaultPda,
+          feeReceiver: feeReceiverKeypair.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([player1Keypair])
+        .rpc();
+
+      for (const joiner of [player2Keypair, player3Keypair]) {
         await program.methods
-          .adminWithdrawPlayer(
-            gameId,
-            player1Keypair.publicKey,
-            new anchor.BN(5000)
-          )
+          .joinGame(gameId, null)
           .accounts({
-            game: game2Pda,
+            game: gamePda,
+            bracket: bracketPda,
+            standings: null,
             gameConfig: gameConfigPda,
-            admin: player1Keypair.publicKey, // Non-admin
+            player: joiner.publicKey,
+            payer: joiner.publicKey,
+            playerProfile: profilePda(joiner.publicKey),
+            blocked: blockedPda(joiner.publicKey),
+            referrerProfile: null,
+            playerBalance: null,
             vault: vaultPda,
+            feeReceiver: feeReceiverKeypair.publicKey,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            systemProgram: anchor.web3.SystemProgram.programId,
           })
-          .remainingAccounts([
-            {
-              pubkey: player1Keypair.publicKey,
-              isWritable: true,
-              isSigner: false,
-            }
-          ])
-          .signers([player1Keypair])
+          .signers([joiner])
           .rpc();
-        
-        expect.fail("Should have failed with UnauthorizedAccess");
-      } catch (error) {
-        expect(error.toString()).to.include("UnauthorizedAccess");
-        console.log("✅ Correctly rejected non-admin access");
       }
-    });
-  });
 
-  describe("Configuration Updates", () => {
-    it("Admin can update contract configuration", async () => {
-      const tx = await program.methods
-        .updateConfig(
-          100, // Change fee to 1%
-          null, null, null, null
-        )
+      await program.methods
+        .adminStartGame(gameId)
         .accounts({
+          game: gamePda,
+          bracket: bracketPda,
+          standings: null,
           gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
           admin: adminKeypair.publicKey,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
         })
         .signers([adminKeypair])
         .rpc();
 
-      console.log("✅ Updated config:", tx);
+      const game = await program.account.game.fetch(gamePda);
+      expect(game.status).to.deep.equal({ active: {} });
 
-      // Verify configuration update
-      const config = await program.account.gameConfig.fetch(gameConfigPda);
-      expect(config.feeBps).to.equal(100);
+      // Top seed skips the first round; the other semifinal is played
+      let bracket = await program.account.bracket.fetch(bracketPda);
+      expect(bracket.byes).to.have.length(1);
+      expect(bracket.byes[0].toString()).to.equal(bracket.players[0].toString());
+      expect(bracket.matches[0].status).to.deep.equal({ completed: {} });
+      expect(bracket.matches[1].status).to.deep.equal({ ready: {} });
+
+      const advance = async (matchIndex: number, winner: PublicKey) =>
+        program.methods
+          .advanceWinner(gameId, matchIndex, winner)
+          .accounts({
+            game: gamePda,
+            bracket: bracketPda,
+            gameConfig: gameConfigPda,
+            adminActionLog: adminActionLogPda,
+            admin: adminKeypair.publicKey,
+          })
+          .signers([adminKeypair])
+          .rpc();
+
+      await advance(1, bracket.matches[1].players[0]);
+
+      // The lone semifinal loser takes third place without a match
+      bracket = await program.account.bracket.fetch(bracketPda);
+      expect(bracket.matches[3].status).to.deep.equal({ completed: {} });
+      expect(bracket.byes).to.have.length(2);
+
+      await advance(2, bracket.matches[2].players[0]);
+
+      bracket = await program.account.bracket.fetch(bracketPda);
+      expect(bracket.completed).to.equal(true);
+      expect(bracket.placements.filter((p) => p !== null)).to.have.length(3);
+
+      const [first, second, third] = bracket.placements;
+      await program.methods
+        .adminPayoutWinners(
+          gameId,
+          [
+            { player: first, place: 1 },
+            { player: second, place: 2 },
+            { player: third, place: 3 },
+          ],
+          new anchor.BN(0)
+        )
+        .accounts({
+          game: gamePda,
+          bracket: bracketPda,
+          standings: null,
+          gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
+          admin: adminKeypair.publicKey,
+          vault: vaultPda,
+          gasReimbursement: feeReceiverKeypair.publicKey,
+          rentPayer: player1Keypair.publicKey,
+        })
+        .remainingAccounts(await settlementAccounts(gamePda, [first, second, third]))
+        .signers([adminKeypair])
+        .rpc();
+      await releaseActiveGames(gamePda);
+
+      console.log("✅ Under-filled tournament completed with byes");
+    });
+
+    it("Double elimination runs through the losers bracket and a bracket reset", async () => {
+      const config = await program.account.gameConfig.fetch(gameConfigPda);
+      const gameId = config.nextGameId;
+
+      const [gamePda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("game"), gameId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [bracketPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("bracket"), gameId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      await program.methods
+        .createGame({ doubleElimination: { size: 4, bracketReset: true } }, { pointOne: {} }, null)
+        .accounts({
+          game: gamePda,
+          bracket: bracketPda,
+          standings: null,
+          gameConfig: gameConfigPda,
+          creator: player1Keypair.publicKey,
+          payer: player1Keypair.publicKey,
+          creatorProfile: profilePda(player1Keypair.publicKey),
+          blocked: blockedPda(player1Keypair.publicKey),
+          referrerProfile: null,
+          playerBalance: null,
+          vault: vaultPda,
+          feeReceiver: feeReceiverKeypair.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([player1Keypair])
+        .rpc();
+
+      for (const joiner of [player2Keypair, player3Keypair, player4Keypair]) {
+        if (joiner === player4Keypair) {
+          // Three entrants would need a bye, which double elimination does not support
+          try {
+            await program.methods
+              .adminStartGame(gameId)
+              .accounts({
+                game: gamePda,
+                bracket: bracketPda,
+                standings: null,
+                gameConfig: gameConfigPda,
+                adminActionLog: adminActionLogPda,
+                admin: adminKeypair.publicKey,
+                slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+              })
+              .signers([adminKeypair])
+              .rpc();
+            expect.fail("Should have failed with DoubleEliminationByes");
+          } catch (error) {
+            expect(error.toString()).to.include("DoubleEliminationByes");
+          }
+        }
+
+        await program.methods
+          .joinGame(gameId, null)
+          .accounts({
+            game: gamePda,
+            bracket: bracketPda,
+            standings: null,
+            gameConfig: gameConfigPda,
+            player: joiner.publicKey,
+            payer: joiner.publicKey,
+            playerProfile: profilePda(joiner.publicKey),
+            blocked: blockedPda(joiner.publicKey),
+            referrerProfile: null,
+            playerBalance: null,
+            vault: vaultPda,
+            feeReceiver: feeReceiverKeypair.publicKey,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([joiner])
+          .rpc();
+      }
+
+      // Winners bracket (3), losers bracket (2), grand final and reset match
+      let bracket = await program.account.bracket.fetch(bracketPda);
+      expect(bracket.matches).to.have.length(7);
+      expect(bracket.grandFinal).to.equal(5);
+      expect(bracket.resetMatch).to.equal(6);
+
+      // Slot 1 wins every match, so the losers-bracket champion forces the reset
+      let played = 0;
+      while (!bracket.completed) {
+        const index = bracket.matches.findIndex((m) => "ready" in m.status);
+        await program.methods
+          .advanceWinner(gameId, index, bracket.matches[index].players[1])
+          .accounts({
+            game: gamePda,
+            bracket: bracketPda,
+            gameConfig: gameConfigPda,
+            adminActionLog: adminActionLogPda,
+            admin: adminKeypair.publicKey,
+          })
+          .signers([adminKeypair])
+          .rpc();
+        played++;
+        bracket = await program.account.bracket.fetch(bracketPda);
+      }
+
+      expect(played).to.equal(7);
+      const reset = bracket.matches[6];
+      expect(bracket.placements[0].toString()).to.equal(reset.winner.toString());
+      expect(bracket.placements[1].toString()).to.equal(reset.players[0].toString());
+      expect(bracket.placements[2].toString()).to.equal(bracket.matches[4].players[0].toString());
+
+      console.log("✅ Double elimination completed after bracket reset");
+    });
+  });
+
+  describe("Swiss Tournament", () => {
+    it("Pairs by score without rematches and pays out final standings", async () => {
+      const config = await program.account.gameConfig.fetch(gameConfigPda);
+      const gameId = config.nextGameId;
+
+      const [gamePda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("game"), gameId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [standingsPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("standings"), gameId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      await program.methods
+        .createGame({ swiss: { size: 4, rounds: 2 } }, { pointOne: {} }, null)
+        .accounts({
+          game: gamePda,
+          bracket: null,
+          standings: standingsPda,
+          gameConfig: gameConfigPda,
+          creator: player1Keypair.publicKey,
+          payer: player1Keypair.publicKey,
+          creatorProfile: profilePda(player1Keypair.publicKey),
+          blocked: blockedPda(player1Keypair.publicKey),
+          referrerProfile: null,
+          playerBalance: null,
+          vault: vaultPda,
+          feeReceiver: feeReceiverKeypair.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([player1Keypair])
+        .rpc();
+
+      for (const joiner of [player2Keypair, player3Keypair, player4Keypair]) {
+        await program.methods
+          .joinGame(gameId, null)
+          .accounts({
+            game: gamePda,
+            bracket: null,
+            standings: standingsPda,
+            gameConfig: gameConfigPda,
+            player: joiner.publicKey,
+            payer: joiner.publicKey,
+            playerProfile: profilePda(joiner.publicKey),
+            blocked: blockedPda(joiner.publicKey),
+            referrerProfile: null,
+            playerBalance: null,
+            vault: vaultPda,
+            feeReceiver: feeReceiverKeypair.publicKey,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([joiner])
+          .rpc();
+      }
+
+      let standings = await program.account.standings.fetch(standingsPda);
+      expect(standings.round).to.equal(1);
+      expect(standings.pairings).to.have.length(2);
+
+      const met = new Set<string>();
+      while (!standings.completed) {
+        const round = standings.round;
+        for (const [index, pairing] of standings.pairings.entries()) {
+          // Winners of round 1 meet each other in round 2
+          if (round === 2) {
+            expect(standings.entries[pairing.a].points).to.equal(standings.entries[pairing.b].points);
+          }
+          const key = [pairing.a, pairing.b].sort().join("-");
+          expect(met.has(key)).to.equal(false);
+          met.add(key);
+
+          await program.methods
+            .recordSwissResult(gameId, index, standings.entries[pairing.a].player)
+            .accounts({
+              game: gamePda,
+              standings: standingsPda,
+              gameConfig: gameConfigPda,
+              adminActionLog: adminActionLogPda,
+              admin: adminKeypair.publicKey,
+            })
+            .signers([adminKeypair])
+            .rpc();
+        }
+        standings = await program.account.standings.fetch(standingsPda);
+      }
+
+      const [first, second, third] = standings.placements;
+      const points = (player: PublicKey) =>
+        standings.entries.find((e) => e.player.equals(player)).points;
+      expect(points(first)).to.equal(2);
+      expect(points(second)).to.equal(1);
+      expect(points(third)).to.equal(1);
+
+      await program.methods
+        .adminPayoutWinners(
+          gameId,
+          [
+            { player: first, place: 1 },
+            { player: second, place: 2 },
+            { player: third, place: 3 },
+          ],
+          new anchor.BN(0)
+        )
+        .accounts({
+          game: gamePda,
+          bracket: null,
+          standings: standingsPda,
+          gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
+          admin: adminKeypair.publicKey,
+          vault: vaultPda,
+          gasReimbursement: feeReceiverKeypair.publicKey,
+          rentPayer: player1Keypair.publicKey,
+        })
+        .remainingAccounts(await settlementAccounts(gamePda, [first, second, third]))
+        .signers([adminKeypair])
+        .rpc();
+      await releaseActiveGames(gamePda);
+
+      const game = await program.account.game.fetch(gamePda);
+      expect(game.status).to.deep.equal({ completed: {} });
+
+      console.log("✅ Swiss standings paid out");
+    });
+  });
+
+  describe("League", () => {
+    it("Records every pairing once and pays out final standings", async () => {
+      const config = await program.account.gameConfig.fetch(gameConfigPda);
+      const gameId = config.nextGameId;
+
+      const [gamePda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("game"), gameId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [standingsPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("standings"), gameId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const leagueMatchPda = (a: number, b: number) =>
+        PublicKey.findProgramAddressSync(
+          [Buffer.from("league_match"), gameId.toArrayLike(Buffer, "le", 8), Buffer.from([a]), Buffer.from([b])],
+          program.programId
+        )[0];
+
+      await program.methods
+        .createGame({ league: { size: 3 } }, { pointOne: {} }, null)
+        .accounts({
+          game: gamePda,
+          bracket: null,
+          standings: standingsPda,
+          gameConfig: gameConfigPda,
+          creator: player1Keypair.publicKey,
+          payer: player1Keypair.publicKey,
+          creatorProfile: profilePda(player1Keypair.publicKey),
+          blocked: blockedPda(player1Keypair.publicKey),
+          referrerProfile: null,
+          playerBalance: null,
+          vault: vaultPda,
+          feeReceiver: feeReceiverKeypair.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([player1Keypair])
+        .rpc();
+
+      for (const joiner of [player2Keypair, player3Keypair]) {
+        await program.methods
+          .joinGame(gameId, null)
+          .accounts({
+            game: gamePda,
+            bracket: null,
+            standings: standingsPda,
+            gameConfig: gameConfigPda,
+            player: joiner.publicKey,
+            payer: joiner.publicKey,
+            playerProfile: profilePda(joiner.publicKey),
+            blocked: blockedPda(joiner.publicKey),
+            referrerProfile: null,
+            playerBalance: null,
+            vault: vaultPda,
+            feeReceiver: feeReceiverKeypair.publicKey,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([joiner])
+          .rpc();
+      }
+
+      let standings = await program.account.standings.fetch(standingsPda);
+      const record = (a: number, b: number, winner: PublicKey) =>
+        program.methods
+          .recordLeagueResult(gameId, a, b, winner)
+          .accounts({
+            game: gamePda,
+            standings: standingsPda,
+            leagueMatch: leagueMatchPda(a, b),
+            gameConfig: gameConfigPda,
+            adminActionLog: adminActionLogPda,
+            admin: adminKeypair.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([adminKeypair])
+          .rpc();
+
+      // The lower-seeded entry wins every pairing
+      for (const [a, b] of [[0, 1], [0, 2], [1, 2]]) {
+        await record(a, b, standings.entries[a].player);
+      }
+
+      const leagueMatch = await program.account.leagueMatch.fetch(leagueMatchPda(0, 1));
+      expect(leagueMatch.winner.toString()).to.equal(standings.entries[0].player.toString());
+
+      try {
+        await record(0, 1, standings.entries[1].player);
+        expect.fail("A pairing cannot be recorded twice");
+      } catch (error) {
+        expect(error.toString()).to.include("already in use");
+      }
+
+      standings = await program.account.standings.fetch(standingsPda);
+      expect(standings.completed).to.equal(true);
+      expect(standings.entries.map((e) => e.points)).to.deep.equal([2, 1, 0]);
+      expect(standings.placements.map((p) => p.toString())).to.deep.equal(
+        standings.entries.map((e) => e.player.toString())
+      );
+
+      const [first, second, third] = standings.placements;
+      await program.methods
+        .adminPayoutWinners(
+          gameId,
+          [
+            { player: first, place: 1 },
+            { player: second, place: 2 },
+            { player: third, place: 3 },
+          ],
+          new anchor.BN(0)
+        )
+        .accounts({
+          game: gamePda,
+          bracket: null,
+          standings: standingsPda,
+          gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
+          admin: adminKeypair.publicKey,
+          vault: vaultPda,
+          gasReimbursement: feeReceiverKeypair.publicKey,
+          rentPayer: player1Keypair.publicKey,
+        })
+        .remainingAccounts(await settlementAccounts(gamePda, [first, second, third]))
+        .signers([adminKeypair])
+        .rpc();
+      await releaseActiveGames(gamePda);
+
+      console.log("✅ League standings paid out");
+    });
+  });
+
+  describe("Admin Functions", () => {
+    it("Create game for admin tests", async () => {
+      // Create a game for admin function testing
+      const { gameId, gamePda } = await createOneVsOne(player3Keypair);
+      game3Pda = gamePda;
+
+      console.log("Created admin test game with ID:", gameId.toString());
+      console.log("Game3 PDA:", game3Pda.toString());
+
+      console.log("✅ Created game for admin tests");
+    });
+
+    it("Admin can withdraw player before game starts", async () => {
+      // Get the actual game ID from the game state
+      const game3 = await program.account.game.fetch(game3Pda);
+      const gameId = game3.id;
+
+      const player3BalanceBefore = await provider.connection.getBalance(player3Keypair.publicKey);
+      const vaultBalanceBefore = await provider.connection.getBalance(vaultPda);
+      const reimbursementBefore = await provider.connection.getBalance(feeReceiverKeypair.publicKey);
+
+      const gasFee = 5000;
+      const tx = await program.methods
+        .adminWithdrawPlayer(
+          gameId,
+          player3Keypair.publicKey,
+          new anchor.BN(gasFee)
+        )
+        .accounts({
+          game: game3Pda,
+          gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
+          admin: adminKeypair.publicKey,
+          playerProfile: profilePda(player3Keypair.publicKey),
+          vault: vaultPda,
+          gasReimbursement: feeReceiverKeypair.publicKey,
+        })
+        .remainingAccounts([
+          {
+            pubkey: player3Keypair.publicKey,
+            isWritable: true,
+            isSigner: false,
+          }
+        ])
+        .signers([adminKeypair])
+        .rpc();
+
+      console.log("✅ Admin withdrew player:", tx);
+
+      // Verify game state
+      const game = await program.account.game.fetch(game3Pda);
+      expect(game.players).to.have.length(0);
+      expect(game.totalPot.toString()).to.equal("0");
+      expect(game.gasFeesDeducted.toNumber()).to.equal(gasFee);
+
+      // Verify balance changes
+      const player3BalanceAfter = await provider.connection.getBalance(player3Keypair.publicKey);
+      const vaultBalanceAfter = await provider.connection.getBalance(vaultPda);
+
+      const stakeAmount = 0.1 * LAMPORTS_PER_SOL;
+      const refundAmount = stakeAmount - gasFee;
+
+      expect(player3BalanceAfter - player3BalanceBefore).to.equal(refundAmount);
+      // The deducted gas leaves the vault for the reimbursement account
+      expect(vaultBalanceBefore - vaultBalanceAfter).to.equal(stakeAmount);
+      const reimbursementAfter = await provider.connection.getBalance(feeReceiverKeypair.publicKey);
+      expect(reimbursementAfter - reimbursementBefore).to.equal(gasFee);
+    });
+
+    it("Admin can payout winners (1v1 game)", async () => {
+      // Get the actual game ID from the game state
+      const game1 = await program.account.game.fetch(game1Pda);
+      const gameId = game1.id;
+
+      const winnerBalanceBefore = await provider.connection.getBalance(player1Keypair.publicKey);
+      const vaultBalanceBefore = await provider.connection.getBalance(vaultPda);
+      const winnerProfileBefore = await program.account.playerProfile.fetch(profilePda(player1Keypair.publicKey));
+      const loserProfileBefore = await program.account.playerProfile.fetch(profilePda(player2Keypair.publicKey));
+
+      const gasFeePerWinner = 5000;
+      const winners = [
+        { player: player1Keypair.publicKey, place: 1 }
+      ];
+
+      const tx = await program.methods
+        .adminPayoutWinners(
+          gameId,
+          winners,
+          new anchor.BN(gasFeePerWinner)
+        )
+        .accounts({
+          game: game1Pda,
+          bracket: null,
+          standings: null,
+          gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
+          admin: adminKeypair.publicKey,
+          vault: vaultPda,
+          gasReimbursement: feeReceiverKeypair.publicKey,
+          rentPayer: null,
+        })
+        .remainingAccounts(await settlementAccounts(game1Pda, [player1Keypair.publicKey]))
+        .signers([adminKeypair])
+        .rpc();
+      await releaseActiveGames(game1Pda);
+
+      console.log("✅ Admin paid out 1v1 winner:", tx);
+
+      // Verify game completion
+      const game = await program.account.game.fetch(game1Pda);
+      expect(game.status).to.deep.equal({ completed: {} });
+
+      // Verify payout
+      const winnerBalanceAfter = await provider.connection.getBalance(player1Keypair.publicKey);
+      const vaultBalanceAfter = await provider.connection.getBalance(vaultPda);
+
+      const totalPot = 0.2 * LAMPORTS_PER_SOL;
+      const payout = totalPot - gasFeePerWinner;
+
+      expect(winnerBalanceAfter - winnerBalanceBefore).to.equal(payout);
+      expect(vaultBalanceBefore - vaultBalanceAfter).to.equal(totalPot);
+
+      // Both profiles count the settled game; only the winner gets the win and the prize
+      const winnerProfile = await program.account.playerProfile.fetch(profilePda(player1Keypair.publicKey));
+      const loserProfile = await program.account.playerProfile.fetch(profilePda(player2Keypair.publicKey));
+      expect(winnerProfile.gamesPlayed - winnerProfileBefore.gamesPlayed).to.equal(1);
+      expect(winnerProfile.wins - winnerProfileBefore.wins).to.equal(1);
+      expect(winnerProfile.podiumFinishes - winnerProfileBefore.podiumFinishes).to.equal(1);
+      expect(winnerProfile.lamportsWon.sub(winnerProfileBefore.lamportsWon).toNumber()).to.equal(payout);
+      expect(winnerProfile.lamportsStaked.sub(winnerProfileBefore.lamportsStaked).toNumber())
+        .to.equal(0.1 * LAMPORTS_PER_SOL);
+      expect(loserProfile.gamesPlayed - loserProfileBefore.gamesPlayed).to.equal(1);
+      expect(loserProfile.wins).to.equal(loserProfileBefore.wins);
+      expect(loserProfile.lamportsWon.toString()).to.equal(loserProfileBefore.lamportsWon.toString());
+      expect(game.gasFeesDeducted.toNumber()).to.equal(gasFeePerWinner);
+
+      // The payout is the latest entry in the admin action log
+      const log = await program.account.adminActionLog.fetch(adminActionLogPda);
+      const latest = log.entries[(log.nextIndex + log.entries.length - 1) % log.entries.length];
+      expect(latest.actor.toString()).to.equal(adminKeypair.publicKey.toString());
+      expect(latest.action).to.deep.equal({ payoutWinners: {} });
+      expect(latest.gameId.toString()).to.equal(gameId.toString());
+      expect(latest.amount.toNumber()).to.equal(payout);
+      expect(latest.gasDeducted.toNumber()).to.equal(gasFeePerWinner);
+    });
+
+    it("Completed games cannot be cancelled and refunded again", async () => {
+      const game1 = await program.account.game.fetch(game1Pda);
+
+      try {
+        await program.methods
+          .adminCancelGame(game1.id, new anchor.BN(0))
+          .accounts({
+            game: game1Pda,
+            bracket: null,
+            standings: null,
+            gameConfig: gameConfigPda,
+            adminActionLog: adminActionLogPda,
+            admin: adminKeypair.publicKey,
+            vault: vaultPda,
+            gasReimbursement: feeReceiverKeypair.publicKey,
+            rentPayer: null,
+            previousGame: null,
+          })
+          .remainingAccounts(await refundAccounts(game1Pda))
+          .signers([adminKeypair])
+          .rpc();
+        expect.fail("Should have failed with GameAlreadyCompleted");
+      } catch (error) {
+        expect(error.toString()).to.include("GameAlreadyCompleted");
+      }
+    });
+
+    it("A player's slot in a settled game is released only once", async () => {
+      const game1 = await program.account.game.fetch(game1Pda);
+
+      try {
+        await program.methods
+          .releaseActiveGame(game1.id, player1Keypair.publicKey)
+          .accounts({
+            game: game1Pda,
+            playerProfile: profilePda(player1Keypair.publicKey),
+          })
+          .rpc();
+        expect.fail("Should have failed with ProfileAlreadyReleased");
+      } catch (error) {
+        expect(error.toString()).to.include("ProfileAlreadyReleased");
+      }
+    });
+
+    it("Non-admin cannot call admin functions", async () => {
+      // Get the actual game ID from the game state
+      const game2 = await program.account.game.fetch(game2Pda);
+      const gameId = game2.id;
+
+      try {
+        await program.methods
+          .adminWithdrawPlayer(
+            gameId,
+            player1Keypair.publicKey,
+            new anchor.BN(5000)
+          )
+          .accounts({
+            game: game2Pda,
+            gameConfig: gameConfigPda,
+            adminActionLog: adminActionLogPda,
+            admin: player1Keypair.publicKey, // Non-admin
+            playerProfile: profilePda(player1Keypair.publicKey),
+            vault: vaultPda,
+            gasReimbursement: feeReceiverKeypair.publicKey,
+          })
+          .remainingAccounts([
+            {
+              pubkey: player1Keypair.publicKey,
+              isWritable: true,
+              isSigner: false,
+            }
+          ])
+          .signers([player1Keypair])
+          .rpc();
+        
+        expect.fail("Should have failed with UnauthorizedAccess");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAccess");
+        console.log("✅ Correctly rejected non-admin access");
+      }
+    });
+
+    it("Gas fee above the configured maximum is rejected", async () => {
+      const config = await program.account.gameConfig.fetch(gameConfigPda);
+      const game2 = await program.account.game.fetch(game2Pda);
+
+      try {
+        await program.methods
+          .adminCancelGame(game2.id, config.maxGasFeeLamports.addn(1))
+          .accounts({
+            game: game2Pda,
+            bracket: bracket2Pda,
+            standings: null,
+            gameConfig: gameConfigPda,
+            adminActionLog: adminActionLogPda,
+            admin: adminKeypair.publicKey,
+            vault: vaultPda,
+            gasReimbursement: feeReceiverKeypair.publicKey,
+            rentPayer: player2Keypair.publicKey,
+            previousGame: null,
+          })
+          .signers([adminKeypair])
+          .rpc();
+
+        expect.fail("Should have failed with GasFeeTooHigh");
+      } catch (error) {
+        expect(error.toString()).to.include("GasFeeTooHigh");
+        console.log("✅ Correctly capped the gas deduction");
+      }
+    });
+  });
+
+  describe("Configuration Updates", () => {
+    it("Admin can lower the fee immediately", async () => {
+      const tx = await program.methods
+        .updateConfig(25) // Lower fee to 0.25%
+        .accounts({
+          gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
+          admin: adminKeypair.publicKey,
+        })
+        .signers([adminKeypair])
+        .rpc();
+
+      console.log("✅ Updated config:", tx);
+
+      const config = await program.account.gameConfig.fetch(gameConfigPda);
+      expect(config.feeBps).to.equal(25);
       expect(config.firstPlacePercentage).to.equal(50); // Unchanged
     });
 
-    it("Non-admin cannot update configuration", async () => {
+    it("Fee increases cannot skip the timelock", async () => {
+      try {
+        await program.methods
+          .updateConfig(100)
+          .accounts({
+            gameConfig: gameConfigPda,
+            adminActionLog: adminActionLogPda,
+            admin: adminKeypair.publicKey,
+          })
+          .signers([adminKeypair])
+          .rpc();
+
+        expect.fail("Should have failed with FeeIncreaseRequiresTimelock");
+      } catch (error) {
+        expect(error.toString()).to.include("FeeIncreaseRequiresTimelock");
+      }
+    });
+
+    it("Non-admin cannot update configuration", async () => {
+      try {
+        await program.methods
+          .updateConfig(10)
+          .accounts({
+            gameConfig: gameConfigPda,
+            adminActionLog: adminActionLogPda,
+            admin: player1Keypair.publicKey, // Non-admin
+          })
+          .signers([player1Keypair])
+          .rpc();
+        
+        expect.fail("Should have failed with UnauthorizedAccess");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAccess");
+        console.log("✅ Correctly rejected non-admin config update");
+      }
+    });
+
+    it("Queued change applies only after the timelock", async () => {
+      await queueConfigChange({ feeBps: 100, prizePercentages: [60, 25, 15] });
+
+      const pending = await program.account.pendingConfigChange.fetch(pendingChangePda);
+      expect(pending.feeBps).to.equal(100);
+      expect(pending.executableAt.sub(pending.queuedAt).toNumber()).to.equal(CONFIG_TIMELOCK_SECS);
+
+      let config = await program.account.gameConfig.fetch(gameConfigPda);
+      expect(config.feeBps).to.equal(25);
+
+      try {
+        await executeConfigChange();
+        expect.fail("Should have failed with TimelockNotElapsed");
+      } catch (error) {
+        expect(error.toString()).to.include("TimelockNotElapsed");
+      }
+
+      await new Promise(resolve => setTimeout(resolve, (CONFIG_TIMELOCK_SECS + 2) * 1000));
+      await executeConfigChange();
+
+      config = await program.account.gameConfig.fetch(gameConfigPda);
+      expect(config.feeBps).to.equal(100);
+      expect(config.firstPlacePercentage).to.equal(60);
+      expect(config.secondPlacePercentage).to.equal(25);
+      expect(config.thirdPlacePercentage).to.equal(15);
+      expect(await provider.connection.getAccountInfo(pendingChangePda)).to.be.null;
+    });
+
+    it("Queued change can be cancelled", async () => {
+      await queueConfigChange({ prizePercentages: [50, 30, 20] });
+
+      await program.methods
+        .cancelConfigChange()
+        .accounts({
+          pendingChange: pendingChangePda,
+          gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
+          admin: adminKeypair.publicKey,
+          proposer: adminKeypair.publicKey,
+        })
+        .signers([adminKeypair])
+        .rpc();
+
+      const config = await program.account.gameConfig.fetch(gameConfigPda);
+      expect(config.firstPlacePercentage).to.equal(60);
+      expect(await provider.connection.getAccountInfo(pendingChangePda)).to.be.null;
+    });
+
+    it("Invalid queued changes are rejected", async () => {
+      try {
+        await queueConfigChange({ prizePercentages: [50, 30, 30] });
+        expect.fail("Should have failed with InvalidPrizeDistribution");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidPrizeDistribution");
+      }
+
+      try {
+        await queueConfigChange({ maxActiveGamesPerPlayer: 0 });
+        expect.fail("Should have failed with InvalidActiveGameLimit");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidActiveGameLimit");
+      }
+    });
+
+    it("Players cannot enter more games than the active game limit", async () => {
+      // player4 is still in the unsettled double-elimination game
+      const profile = await program.account.playerProfile.fetch(profilePda(player4Keypair.publicKey));
+      expect(profile.activeGames).to.be.greaterThan(0);
+      const defaultLimit = (await program.account.gameConfig.fetch(gameConfigPda)).maxActiveGamesPerPlayer;
+
+      await applyConfigChange({ maxActiveGamesPerPlayer: profile.activeGames });
+
+      const config = await program.account.gameConfig.fetch(gameConfigPda);
+      expect(config.maxActiveGamesPerPlayer).to.equal(profile.activeGames);
+
+      try {
+        await createOneVsOne(player4Keypair);
+        expect.fail("Should have failed with TooManyActiveGames");
+      } catch (error) {
+        expect(error.toString()).to.include("TooManyActiveGames");
+        console.log("✅ Correctly enforced the active game limit");
+      }
+
+      await applyConfigChange({ maxActiveGamesPerPlayer: defaultLimit });
+    });
+  });
+
+  describe("Active Game Limit", () => {
+    const player = Keypair.generate();
+    let gameId: anchor.BN;
+    let gamePda: PublicKey;
+
+    const resync = (games: PublicKey[]) =>
+      program.methods
+        .resyncActiveGames(player.publicKey)
+        .accounts({
+          playerProfile: profilePda(player.publicKey),
+          gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
+          admin: adminKeypair.publicKey,
+        })
+        .remainingAccounts(games.map((pubkey) => ({ pubkey, isWritable: false, isSigner: false })))
+        .signers([adminKeypair])
+        .rpc();
+
+    const activeGames = async () =>
+      (await program.account.playerProfile.fetch(profilePda(player.publicKey))).activeGames;
+
+    before(async () => {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(player.publicKey, LAMPORTS_PER_SOL)
+      );
+
+      ({ gameId, gamePda } = await createOneVsOne(player));
+    });
+
+    it("Resync recounts a player's open games", async () => {
+      for (const [games, error] of [
+        [[gamePda, gamePda], "DuplicateGameAccount"],
+        [[game1Pda], "PlayerNotInGame"],
+      ] as [PublicKey[], string][]) {
+        try {
+          await resync(games);
+          expect.fail(`Should have failed with ${error}`);
+        } catch (e) {
+          expect(e.toString()).to.include(error);
+        }
+      }
+
+      await resync([]);
+      expect(await activeGames()).to.equal(0);
+
+      await resync([gamePda]);
+      expect(await activeGames()).to.equal(1);
+    });
+
+    it("A settled game holds the slot until it is released", async () => {
+      await program.methods
+        .adminCancelGame(gameId, new anchor.BN(0))
+        .accounts({
+          game: gamePda,
+          bracket: null,
+          standings: null,
+          gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
+          admin: adminKeypair.publicKey,
+          vault: vaultPda,
+          gasReimbursement: feeReceiverKeypair.publicKey,
+          rentPayer: null,
+          previousGame: null,
+        })
+        .remainingAccounts(await refundAccounts(gamePda))
+        .signers([adminKeypair])
+        .rpc();
+
+      await resync([gamePda]);
+      expect(await activeGames()).to.equal(1);
+
+      await releaseActiveGames(gamePda);
+      expect(await activeGames()).to.equal(0);
+
+      try {
+        await resync([gamePda]);
+        expect.fail("Should have failed with GameNotOpen");
+      } catch (error) {
+        expect(error.toString()).to.include("GameNotOpen");
+      }
+    });
+  });
+
+  describe("Roles", () => {
+    const roleSeeds = { config: 0, settlement: 1, pauser: 2, treasury: 3, blocklist: 4 };
+
+    const proposalPda = (role: string) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("authority_proposal"), Buffer.from([roleSeeds[role]])],
+        program.programId
+      )[0];
+
+    const proposeAuthority = (role: string, newAuthority: PublicKey, proposer: Keypair) =>
+      program.methods
+        .proposeAuthority({ [role]: {} } as any, newAuthority)
+        .accounts({
+          proposal: proposalPda(role),
+          gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
+          proposer: proposer.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([proposer])
+        .rpc();
+
+    const acceptAuthority = (role: string, newAuthority: Keypair, proposer: PublicKey) =>
+      program.methods
+        .acceptAuthority({ [role]: {} } as any)
+        .accounts({
+          proposal: proposalPda(role),
+          gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
+          newAuthority: newAuthority.publicKey,
+          proposer,
+        })
+        .signers([newAuthority])
+        .rpc();
+
+    const rotate = async (role: string, from: Keypair, to: Keypair) => {
+      await proposeAuthority(role, to.publicKey, from);
+      await acceptAuthority(role, to, from.publicKey);
+    };
+
+    it("Initialize hands every role to the admin", async () => {
+      const config = await program.account.gameConfig.fetch(gameConfigPda);
+      expect(config.settlementAuthority.toString()).to.equal(adminKeypair.publicKey.toString());
+      expect(config.pauser.toString()).to.equal(adminKeypair.publicKey.toString());
+      expect(config.treasuryAuthority.toString()).to.equal(adminKeypair.publicKey.toString());
+      expect(config.blocklistAuthority.toString()).to.equal(adminKeypair.publicKey.toString());
+    });
+
+    it("Settlement operator cannot change config and config authority cannot settle", async () => {
+      const operator = player4Keypair;
+      await rotate("settlement", adminKeypair, operator);
+
+      let config = await program.account.gameConfig.fetch(gameConfigPda);
+      expect(config.settlementAuthority.toString()).to.equal(operator.publicKey.toString());
+
+      try {
+        await program.methods
+          .updateConfig(5)
+          .accounts({
+            gameConfig: gameConfigPda,
+            adminActionLog: adminActionLogPda,
+            admin: operator.publicKey,
+          })
+          .signers([operator])
+          .rpc();
+        expect.fail("Should have failed with UnauthorizedAccess");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAccess");
+      }
+
+      try {
+        const game2 = await program.account.game.fetch(game2Pda);
+        await program.methods
+          .adminCancelGame(game2.id, new anchor.BN(0))
+          .accounts({
+            game: game2Pda,
+            bracket: bracket2Pda,
+            standings: null,
+            gameConfig: gameConfigPda,
+            adminActionLog: adminActionLogPda,
+            admin: adminKeypair.publicKey,
+            vault: vaultPda,
+            gasReimbursement: feeReceiverKeypair.publicKey,
+            rentPayer: player2Keypair.publicKey,
+            previousGame: null,
+          })
+          .signers([adminKeypair])
+          .rpc();
+        expect.fail("Should have failed with UnauthorizedAccess");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAccess");
+      }
+
+      // The settlement operator can hand its own role back
+      await rotate("settlement", operator, adminKeypair);
+      config = await program.account.gameConfig.fetch(gameConfigPda);
+      expect(config.settlementAuthority.toString()).to.equal(adminKeypair.publicKey.toString());
+    });
+
+    it("Proposal does nothing until the new key accepts", async () => {
+      await proposeAuthority("treasury", player2Keypair.publicKey, adminKeypair);
+
+      const proposal = await program.account.authorityProposal.fetch(proposalPda("treasury"));
+      expect(proposal.newAuthority.toString()).to.equal(player2Keypair.publicKey.toString());
+
+      let config = await program.account.gameConfig.fetch(gameConfigPda);
+      expect(config.treasuryAuthority.toString()).to.equal(adminKeypair.publicKey.toString());
+
+      try {
+        await acceptAuthority("treasury", player3Keypair, adminKeypair.publicKey);
+        expect.fail("Should have failed with UnauthorizedAccess");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAccess");
+        console.log("✅ Correctly rejected acceptance by a different key");
+      }
+
+      await program.methods
+        .cancelAuthorityProposal({ treasury: {} } as any)
+        .accounts({
+          proposal: proposalPda("treasury"),
+          gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
+          signer: adminKeypair.publicKey,
+          proposer: adminKeypair.publicKey,
+        })
+        .signers([adminKeypair])
+        .rpc();
+
+      const closed = await provider.connection.getAccountInfo(proposalPda("treasury"));
+      expect(closed).to.be.null;
+
+      config = await program.account.gameConfig.fetch(gameConfigPda);
+      expect(config.treasuryAuthority.toString()).to.equal(adminKeypair.publicKey.toString());
+    });
+
+    it("Only the config authority or the role holder can propose", async () => {
+      try {
+        await proposeAuthority("treasury", player1Keypair.publicKey, player1Keypair);
+        expect.fail("Should have failed with UnauthorizedAccess");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAccess");
+        console.log("✅ Correctly rejected role proposal from non-authority");
+      }
+    });
+  });
+
+  describe("Pause Switches", () => {
+    const PAUSE_CREATE = 1;
+    const PAUSE_JOIN = 2;
+    const PAUSE_SETTLEMENT = 4;
+    const PAUSE_WITHDRAWALS = 8;
+    const PAUSE_DEPOSITS = 16;
+
+    const setPaused = (paused: number, signer: Keypair) =>
+      program.methods
+        .setPaused(paused)
+        .accounts({
+          gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
+          admin: signer.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+
+    it("Only the pauser can set pause flags", async () => {
+      try {
+        await setPaused(PAUSE_CREATE, player1Keypair);
+        expect.fail("Should have failed with UnauthorizedAccess");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAccess");
+      }
+
+      try {
+        await setPaused(32, adminKeypair);
+        expect.fail("Should have failed with InvalidPauseFlags");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidPauseFlags");
+      }
+    });
+
+    it("Paused creation and joining are rejected but refunds still work", async () => {
+      const { gameId, gamePda } = await createOneVsOne(player1Keypair);
+
+      await setPaused(PAUSE_CREATE | PAUSE_JOIN | PAUSE_SETTLEMENT | PAUSE_WITHDRAWALS, adminKeypair);
+      const config = await program.account.gameConfig.fetch(gameConfigPda);
+      expect(config.paused).to.equal(15);
+
+      try {
+        await createOneVsOne(player2Keypair);
+        expect.fail("Should have failed with Paused");
+      } catch (error) {
+        expect(error.toString()).to.include("Paused");
+      }
+
+      try {
+        await program.methods
+          .joinGame(gameId, null)
+          .accounts({
+            game: gamePda,
+            bracket: null,
+            standings: null,
+            gameConfig: gameConfigPda,
+            player: player2Keypair.publicKey,
+            payer: player2Keypair.publicKey,
+            playerProfile: profilePda(player2Keypair.publicKey),
+            blocked: blockedPda(player2Keypair.publicKey),
+            referrerProfile: null,
+            playerBalance: null,
+            vault: vaultPda,
+            feeReceiver: feeReceiverKeypair.publicKey,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([player2Keypair])
+          .rpc();
+        expect.fail("Should have failed with Paused");
+      } catch (error) {
+        expect(error.toString()).to.include("Paused");
+      }
+
+      try {
+        await program.methods
+          .adminEmergencyWithdraw(adminKeypair.publicKey, new anchor.BN(1))
+          .accounts({
+            gameConfig: gameConfigPda,
+            adminActionLog: adminActionLogPda,
+            admin: adminKeypair.publicKey,
+            vault: vaultPda,
+          })
+          .remainingAccounts([{ pubkey: adminKeypair.publicKey, isWritable: true, isSigner: false }])
+          .signers([adminKeypair])
+          .rpc();
+        expect.fail("Should have failed with Paused");
+      } catch (error) {
+        expect(error.toString()).to.include("Paused");
+      }
+
+      // Cancelling refunds the creator even while everything is paused
+      const balanceBefore = await provider.connection.getBalance(player1Keypair.publicKey);
+      await program.methods
+        .adminCancelGame(gameId, new anchor.BN(0))
+        .accounts({
+          game: gamePda,
+          bracket: null,
+          standings: null,
+          gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
+          admin: adminKeypair.publicKey,
+          vault: vaultPda,
+          gasReimbursement: feeReceiverKeypair.publicKey,
+          rentPayer: null,
+          previousGame: null,
+        })
+        .remainingAccounts(await refundAccounts(gamePda))
+        .signers([adminKeypair])
+        .rpc();
+      await releaseActiveGames(gamePda);
+      const balanceAfter = await provider.connection.getBalance(player1Keypair.publicKey);
+      expect(balanceAfter).to.be.greaterThan(balanceBefore);
+
+      await setPaused(0, adminKeypair);
+    });
+
+    it("Paused deposits are rejected", async () => {
+      await setPaused(PAUSE_DEPOSITS, adminKeypair);
+
+      try {
+        await program.methods
+          .depositBalance(new anchor.BN(LAMPORTS_PER_SOL / 10))
+          .accounts({
+            playerBalance: balancePda(player1Keypair.publicKey),
+            gameConfig: gameConfigPda,
+            player: player1Keypair.publicKey,
+            blocked: blockedPda(player1Keypair.publicKey),
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([player1Keypair])
+          .rpc();
+        expect.fail("Should have failed with Paused");
+      } catch (error) {
+        expect(error.toString()).to.include("Paused");
+      }
+
+      await setPaused(0, adminKeypair);
+    });
+  });
+
+  describe("Vault Liabilities", () => {
+    let gameId: anchor.BN;
+    let gamePda: PublicKey;
+
+    const emergencyWithdraw = (amount: anchor.BN) =>
+      program.methods
+        .adminEmergencyWithdraw(adminKeypair.publicKey, amount)
+        .accounts({
+          gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
+          admin: adminKeypair.publicKey,
+          vault: vaultPda,
+        })
+        .remainingAccounts([{ pubkey: adminKeypair.publicKey, isWritable: true, isSigner: false }])
+        .signers([adminKeypair])
+        .rpc();
+
+    it("Open games add their stakes to outstanding liabilities", async () => {
+      const before = await program.account.gameConfig.fetch(gameConfigPda);
+      ({ gameId, gamePda } = await createOneVsOne(player1Keypair));
+
+      const after = await program.account.gameConfig.fetch(gameConfigPda);
+      const game = await program.account.game.fetch(gamePda);
+      expect(after.outstandingLiabilities.sub(before.outstandingLiabilities).toString())
+        .to.equal(game.totalPot.toString());
+    });
+
+    it("Emergency withdraw is limited to the surplus above liabilities", async () => {
+      const config = await program.account.gameConfig.fetch(gameConfigPda);
+      const vaultBalance = await provider.connection.getBalance(vaultPda);
+      const rent = await provider.connection.getMinimumBalanceForRentExemption(0);
+      const surplus = Math.max(vaultBalance - rent - config.outstandingLiabilities.toNumber(), 0);
+
+      try {
+        await emergencyWithdraw(new anchor.BN(surplus + 1));
+        expect.fail("Should have failed with ExceedsVaultSurplus");
+      } catch (error) {
+        expect(error.toString()).to.include("ExceedsVaultSurplus");
+        console.log("✅ Correctly refused to withdraw player stakes");
+      }
+    });
+
+    it("Cancelling a game releases its liabilities", async () => {
+      const before = await program.account.gameConfig.fetch(gameConfigPda);
+      const game = await program.account.game.fetch(gamePda);
+      const profileBefore = await program.account.playerProfile.fetch(profilePda(player1Keypair.publicKey));
+
+      await program.methods
+        .adminCancelGame(gameId, new anchor.BN(0))
+        .accounts({
+          game: gamePda,
+          bracket: null,
+          standings: null,
+          gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
+          admin: adminKeypair.publicKey,
+          vault: vaultPda,
+          gasReimbursement: feeReceiverKeypair.publicKey,
+          rentPayer: null,
+          previousGame: null,
+        })
+        .remainingAccounts(await refundAccounts(gamePda))
+        .signers([adminKeypair])
+        .rpc();
+      await releaseActiveGames(gamePda);
+
+      const after = await program.account.gameConfig.fetch(gameConfigPda);
+      expect(before.outstandingLiabilities.sub(after.outstandingLiabilities).toString())
+        .to.equal(game.totalPot.toString());
+
+      // The refunded player gets the game slot back
+      const profileAfter = await program.account.playerProfile.fetch(profilePda(player1Keypair.publicKey));
+      expect(profileBefore.activeGames - profileAfter.activeGames).to.equal(1);
+    });
+  });
+
+  describe("Multisig Proposals", () => {
+    const [multisigPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("multisig")],
+      program.programId
+    );
+
+    const proposalPda = (id: anchor.BN) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("proposal"), id.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      )[0];
+
+    const createProposal = async (action: object, proposer: Keypair) => {
+      const multisig = await program.account.multisig.fetch(multisigPda);
+      const id = multisig.proposalCount;
+      await program.methods
+        .createProposal(action as any, new anchor.BN(3600))
+        .accounts({
+          proposal: proposalPda(id),
+          multisig: multisigPda,
+          adminActionLog: adminActionLogPda,
+          proposer: proposer.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([proposer])
+        .rpc();
+      return id;
+    };
+
+    const approveProposal = (id: anchor.BN, signer: Keypair) =>
+      program.methods
+        .approveProposal(id)
+        .accounts({
+          proposal: proposalPda(id),
+          multisig: multisigPda,
+          adminActionLog: adminActionLogPda,
+          signer: signer.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+
+    const executeProposal = (id: anchor.BN, executor: Keypair, remaining: PublicKey[] = []) =>
+      program.methods
+        .executeProposal(id)
+        .accounts({
+          proposal: proposalPda(id),
+          multisig: multisigPda,
+          gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
+          vault: vaultPda,
+          pendingChange: null,
+          executor: executor.publicKey,
+        })
+        .remainingAccounts(remaining.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false })))
+        .signers([executor])
+        .rpc();
+
+    const createMultisig = (threshold: number, cosigners: Keypair[]) =>
+      program.methods
+        .createMultisig([adminKeypair.publicKey, player3Keypair.publicKey, player4Keypair.publicKey], threshold)
+        .accounts({
+          multisig: multisigPda,
+          gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
+          admin: adminKeypair.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(cosigners.map((k) => ({ pubkey: k.publicKey, isWritable: false, isSigner: true })))
+        .signers([adminKeypair, ...cosigners])
+        .rpc();
+
+    it("Single-key sets and sets the approvers did not co-sign are rejected", async () => {
+      try {
+        await createMultisig(1, [player3Keypair, player4Keypair]);
+        expect.fail("Should have failed with InvalidSignerSet");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidSignerSet");
+      }
+
+      try {
+        await createMultisig(2, [player3Keypair]);
+        expect.fail("Should have failed with MissingSignerConsent");
+      } catch (error) {
+        expect(error.toString()).to.include("MissingSignerConsent");
+      }
+    });
+
+    it("Config authority sets up a 2-of-3 approval set", async () => {
+      await createMultisig(2, [player3Keypair, player4Keypair]);
+
+      const multisig = await program.account.multisig.fetch(multisigPda);
+      expect(multisig.signers.length).to.equal(3);
+      expect(multisig.threshold).to.equal(2);
+    });
+
+    it("Role rotation executes only after the threshold is met", async () => {
+      const id = await createProposal(
+        { setAuthority: { role: { pauser: {} }, newAuthority: player2Keypair.publicKey } },
+        adminKeypair
+      );
+
+      try {
+        await executeProposal(id, adminKeypair);
+        expect.fail("Should have failed with ThresholdNotMet");
+      } catch (error) {
+        expect(error.toString()).to.include("ThresholdNotMet");
+      }
+
+      try {
+        await approveProposal(id, player1Keypair);
+        expect.fail("Should have failed with NotAnApprover");
+      } catch (error) {
+        expect(error.toString()).to.include("NotAnApprover");
+      }
+
+      await approveProposal(id, player3Keypair);
+
+      try {
+        await approveProposal(id, player3Keypair);
+        expect.fail("Should have failed with AlreadyApproved");
+      } catch (error) {
+        expect(error.toString()).to.include("AlreadyApproved");
+      }
+
+      await executeProposal(id, player4Keypair);
+
+      const config = await program.account.gameConfig.fetch(gameConfigPda);
+      expect(config.pauser.toString()).to.equal(player2Keypair.publicKey.toString());
+
+      try {
+        await executeProposal(id, adminKeypair);
+        expect.fail("Should have failed with ProposalAlreadyExecuted");
+      } catch (error) {
+        expect(error.toString()).to.include("ProposalAlreadyExecuted");
+      }
+    });
+
+    it("Approved proposal can withdraw beyond the surplus", async () => {
+      const amount = new anchor.BN(1000);
+      const id = await createProposal(
+        { withdrawLiabilities: { recipient: player4Keypair.publicKey, amount } },
+        player3Keypair
+      );
+      await approveProposal(id, player4Keypair);
+
+      const balanceBefore = await provider.connection.getBalance(player4Keypair.publicKey);
+      await executeProposal(id, player3Keypair, [player4Keypair.publicKey]);
+      const balanceAfter = await provider.connection.getBalance(player4Keypair.publicKey);
+      expect(balanceAfter - balanceBefore).to.equal(amount.toNumber());
+    });
+  });
+
+  describe("Referrals", () => {
+    const REFERRAL_SHARE_BPS = 2000;
+    let gameId: anchor.BN;
+    let gamePda: PublicKey;
+
+    it("Referred player's fee is split with the referrer", async () => {
+      await applyConfigChange({ referralShareBps: REFERRAL_SHARE_BPS });
+
+      const config = await program.account.gameConfig.fetch(gameConfigPda);
+      expect(config.referralShareBps).to.equal(REFERRAL_SHARE_BPS);
+
+      const feeReceiverBefore = await provider.connection.getBalance(feeReceiverKeypair.publicKey);
+      const referrerBefore = await program.account.playerProfile.fetch(profilePda(player4Keypair.publicKey));
+
+      ({ gameId, gamePda } = await createOneVsOne(player3Keypair, player3Keypair, {
+        referrer: player4Keypair.publicKey,
+      }));
+
+      const feeAmount = Math.floor((0.1 * LAMPORTS_PER_SOL * config.feeBps) / 10000);
+      const share = Math.floor((feeAmount * REFERRAL_SHARE_BPS) / 10000);
+
+      const profile = await program.account.playerProfile.fetch(profilePda(player3Keypair.publicKey));
+      expect(profile.referrer.toString()).to.equal(player4Keypair.publicKey.toString());
+
+      const referrerAfter = await program.account.playerProfile.fetch(profilePda(player4Keypair.publicKey));
+      expect(referrerAfter.referralRewards.sub(referrerBefore.referralRewards).toNumber()).to.equal(share);
+
+      const feeReceiverAfter = await provider.connection.getBalance(feeReceiverKeypair.publicKey);
+      expect(feeReceiverAfter - feeReceiverBefore).to.equal(feeAmount - share);
+    });
+
+    it("Players cannot refer themselves", async () => {
+      try {
+        await program.methods
+          .joinGame(gameId, player4Keypair.publicKey)
+          .accounts({
+            game: gamePda,
+            bracket: null,
+            standings: null,
+            gameConfig: gameConfigPda,
+            player: player4Keypair.publicKey,
+            payer: player4Keypair.publicKey,
+            playerProfile: profilePda(player4Keypair.publicKey),
+            blocked: blockedPda(player4Keypair.publicKey),
+            referrerProfile: null,
+            playerBalance: null,
+            vault: vaultPda,
+            feeReceiver: feeReceiverKeypair.publicKey,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([player4Keypair])
+          .rpc();
+        expect.fail("Should have failed with InvalidReferrer");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidReferrer");
+      }
+    });
+
+    it("Referrer claims accrued rewards from the vault", async () => {
+      const profileBefore = await program.account.playerProfile.fetch(profilePda(player4Keypair.publicKey));
+      const configBefore = await program.account.gameConfig.fetch(gameConfigPda);
+      const balanceBefore = await provider.connection.getBalance(player4Keypair.publicKey);
+      expect(profileBefore.referralRewards.toNumber()).to.be.greaterThan(0);
+
+      await program.methods
+        .claimReferralRewards()
+        .accounts({
+          gameConfig: gameConfigPda,
+          referrerProfile: profilePda(player4Keypair.publicKey),
+          referrer: player4Keypair.publicKey,
+          vault: vaultPda,
+        })
+        .signers([player4Keypair])
+        .rpc();
+
+      const profileAfter = await program.account.playerProfile.fetch(profilePda(player4Keypair.publicKey));
+      const configAfter = await program.account.gameConfig.fetch(gameConfigPda);
+      const balanceAfter = await provider.connection.getBalance(player4Keypair.publicKey);
+      expect(profileAfter.referralRewards.toNumber()).to.equal(0);
+      expect(balanceAfter - balanceBefore).to.equal(profileBefore.referralRewards.toNumber());
+      expect(configBefore.outstandingLiabilities.sub(configAfter.outstandingLiabilities).toString())
+        .to.equal(profileBefore.referralRewards.toString());
+
+      try {
+        await program.methods
+          .claimReferralRewards()
+          .accounts({
+            gameConfig: gameConfigPda,
+            referrerProfile: profilePda(player4Keypair.publicKey),
+            referrer: player4Keypair.publicKey,
+            vault: vaultPda,
+          })
+          .signers([player4Keypair])
+          .rpc();
+        expect.fail("Should have failed with NoReferralRewards");
+      } catch (error) {
+        expect(error.toString()).to.include("NoReferralRewards");
+      }
+
+      // Release player3's game slot
+      await program.methods
+        .adminCancelGame(gameId, new anchor.BN(0))
+        .accounts({
+          game: gamePda,
+          bracket: null,
+          standings: null,
+          gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
+          admin: adminKeypair.publicKey,
+          vault: vaultPda,
+          gasReimbursement: feeReceiverKeypair.publicKey,
+          rentPayer: null,
+          previousGame: null,
+        })
+        .remainingAccounts(await refundAccounts(gamePda))
+        .signers([adminKeypair])
+        .rpc();
+      await releaseActiveGames(gamePda);
+      await applyConfigChange({ referralShareBps: 0 });
+    });
+  });
+
+  describe("Volume Fee Tiers", () => {
+    const TIER_FEE_BPS = 10;
+    const emptyTier = { minVolumeLamports: new anchor.BN(0), feeBps: 0 };
+
+    const setFeeTiers = (feeTiers: { minVolumeLamports: anchor.BN; feeBps: number }[]) =>
+      applyConfigChange({ feeTiers });
+
+    it("Out-of-order tiers are rejected", async () => {
+      try {
+        await setFeeTiers([
+          { minVolumeLamports: new anchor.BN(2 * LAMPORTS_PER_SOL), feeBps: TIER_FEE_BPS },
+          { minVolumeLamports: new anchor.BN(LAMPORTS_PER_SOL), feeBps: TIER_FEE_BPS },
+          emptyTier,
+          emptyTier,
+        ]);
+        expect.fail("Should have failed with InvalidFeeTiers");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidFeeTiers");
+      }
+    });
+
+    it("Players past a volume threshold pay the discounted fee", async () => {
+      // player1 has settled games, a new wallet has none
+      const newcomer = Keypair.generate();
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(newcomer.publicKey, 2 * LAMPORTS_PER_SOL)
+      );
+      const veteran = await program.account.playerProfile.fetch(profilePda(player1Keypair.publicKey));
+      expect(veteran.lamportsStaked.toNumber()).to.be.greaterThan(0);
+
+      await setFeeTiers([{ minVolumeLamports: new anchor.BN(1), feeBps: TIER_FEE_BPS }, emptyTier, emptyTier, emptyTier]);
+
+      const config = await program.account.gameConfig.fetch(gameConfigPda);
+      expect(config.feeBps).to.be.greaterThan(TIER_FEE_BPS);
+
+      const feeReceiverBefore = await provider.connection.getBalance(feeReceiverKeypair.publicKey);
+      const { gameId, gamePda } = await createOneVsOne(player1Keypair);
+      const feeReceiverAfter = await provider.connection.getBalance(feeReceiverKeypair.publicKey);
+      expect(feeReceiverAfter - feeReceiverBefore).to.equal((0.1 * LAMPORTS_PER_SOL * TIER_FEE_BPS) / 10000);
+
+      await program.methods
+        .joinGame(gameId, null)
+        .accounts({
+          game: gamePda,
+          bracket: null,
+          standings: null,
+          gameConfig: gameConfigPda,
+          player: newcomer.publicKey,
+          payer: newcomer.publicKey,
+          playerProfile: profilePda(newcomer.publicKey),
+          blocked: blockedPda(newcomer.publicKey),
+          referrerProfile: null,
+          playerBalance: null,
+          vault: vaultPda,
+          feeReceiver: feeReceiverKeypair.publicKey,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([newcomer])
+        .rpc();
+
+      // Each stake records the rate that player actually paid
+      const game = await program.account.game.fetch(gamePda);
+      expect(game.stakeTerms[0].feeBps).to.equal(TIER_FEE_BPS);
+      expect(game.stakeTerms[1].feeBps).to.equal(config.feeBps);
+
+      await program.methods
+        .adminCancelGame(gameId, new anchor.BN(0))
+        .accounts({
+          game: gamePda,
+          bracket: null,
+          standings: null,
+          gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
+          admin: adminKeypair.publicKey,
+          vault: vaultPda,
+          gasReimbursement: feeReceiverKeypair.publicKey,
+          rentPayer: null,
+          previousGame: null,
+        })
+        .remainingAccounts(await refundAccounts(gamePda))
+        .signers([adminKeypair])
+        .rpc();
+      await releaseActiveGames(gamePda);
+      await setFeeTiers([emptyTier, emptyTier, emptyTier, emptyTier]);
+    });
+  });
+
+  describe("Blocklist", () => {
+    const cheater = Keypair.generate();
+
+    const blockWallet = (wallet: PublicKey, admin: Keypair) =>
+      program.methods
+        .blockWallet(wallet)
+        .accounts({
+          blocked: blockedPda(wallet),
+          gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
+          admin: admin.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+
+    before(async () => {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(cheater.publicKey, 2 * LAMPORTS_PER_SOL)
+      );
+    });
+
+    it("Only the blocklist authority can block wallets", async () => {
+      try {
+        await blockWallet(cheater.publicKey, player1Keypair);
+        expect.fail("Should have failed with UnauthorizedAccess");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAccess");
+      }
+    });
+
+    it("Blocked wallets cannot create games but still get refunds", async () => {
+      const { gameId: openGameId, gamePda: openGamePda } = await createOneVsOne(cheater);
+      await blockWallet(cheater.publicKey, adminKeypair);
+
+      const blocked = await program.account.blocked.fetch(blockedPda(cheater.publicKey));
+      expect(blocked.wallet.toString()).to.equal(cheater.publicKey.toString());
+
+      try {
+        await createOneVsOne(cheater);
+        expect.fail("Should have failed with WalletBlocked");
+      } catch (error) {
+        expect(error.toString()).to.include("WalletBlocked");
+        console.log("✅ Correctly rejected a blocked wallet");
+      }
+
+      // Nor fund an internal balance to enter from later
       try {
         await program.methods
-          .updateConfig(200, null, null, null, null)
+          .depositBalance(new anchor.BN(LAMPORTS_PER_SOL / 10))
           .accounts({
+            playerBalance: balancePda(cheater.publicKey),
             gameConfig: gameConfigPda,
-            admin: player1Keypair.publicKey, // Non-admin
+            player: cheater.publicKey,
+            blocked: blockedPda(cheater.publicKey),
+            systemProgram: anchor.web3.SystemProgram.programId,
           })
-          .signers([player1Keypair])
+          .signers([cheater])
           .rpc();
-        
-        expect.fail("Should have failed with UnauthorizedAccess");
+        expect.fail("Should have failed with WalletBlocked");
       } catch (error) {
-        expect(error.toString()).to.include("UnauthorizedAccess");
-        console.log("✅ Correctly rejected non-admin config update");
+        expect(error.toString()).to.include("WalletBlocked");
+      }
+
+      // The game created before the block still refunds normally
+      const balanceBefore = await provider.connection.getBalance(cheater.publicKey);
+      await program.methods
+        .adminCancelGame(openGameId, new anchor.BN(0))
+        .accounts({
+          game: openGamePda,
+          bracket: null,
+          standings: null,
+          gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
+          admin: adminKeypair.publicKey,
+          vault: vaultPda,
+          gasReimbursement: feeReceiverKeypair.publicKey,
+          rentPayer: null,
+          previousGame: null,
+        })
+        .remainingAccounts(await refundAccounts(openGamePda))
+        .signers([adminKeypair])
+        .rpc();
+      await releaseActiveGames(openGamePda);
+      const balanceAfter = await provider.connection.getBalance(cheater.publicKey);
+      expect(balanceAfter - balanceBefore).to.equal(0.1 * LAMPORTS_PER_SOL);
+    });
+
+    it("Unblocking closes the entry and returns the rent", async () => {
+      await program.methods
+        .unblockWallet(cheater.publicKey)
+        .accounts({
+          blocked: blockedPda(cheater.publicKey),
+          gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
+          admin: adminKeypair.publicKey,
+          blockedBy: adminKeypair.publicKey,
+        })
+        .signers([adminKeypair])
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(blockedPda(cheater.publicKey))).to.be.null;
+    });
+  });
+
+  describe("Sponsored Entries", () => {
+    const sponsor = Keypair.generate();
+    const sponsored = Keypair.generate(); // never funded
+    let gameId: anchor.BN;
+    let gamePda: PublicKey;
+
+    const cancelGame = (remaining: { pubkey: PublicKey; isWritable: boolean; isSigner: boolean }[]) =>
+      program.methods
+        .adminCancelGame(gameId, new anchor.BN(0))
+        .accounts({
+          game: gamePda,
+          bracket: null,
+          standings: null,
+          gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
+          admin: adminKeypair.publicKey,
+          vault: vaultPda,
+          gasReimbursement: feeReceiverKeypair.publicKey,
+          rentPayer: null,
+          previousGame: null,
+        })
+        .remainingAccounts(remaining)
+        .signers([adminKeypair])
+        .rpc();
+
+    before(async () => {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(sponsor.publicKey, 2 * LAMPORTS_PER_SOL)
+      );
+    });
+
+    it("A sponsor funds the stake, fee and rent for another player's seat", async () => {
+      ({ gameId, gamePda } = await createOneVsOne(sponsored, sponsor));
+
+      const game = await program.account.game.fetch(gamePda);
+      expect(game.players[0].player.toString()).to.equal(sponsored.publicKey.toString());
+      expect(game.stakeTerms[0].payer.toString()).to.equal(sponsor.publicKey.toString());
+      expect(await provider.connection.getBalance(sponsored.publicKey)).to.equal(0);
+
+      const profile = await program.account.playerProfile.fetch(profilePda(sponsored.publicKey));
+      expect(profile.activeGames).to.equal(1);
+    });
+
+    it("Refunds go back to the sponsor", async () => {
+      try {
+        await cancelGame([{ pubkey: sponsored.publicKey, isWritable: true, isSigner: false }]);
+        expect.fail("Should have failed with RefundAccountMismatch");
+      } catch (error) {
+        expect(error.toString()).to.include("RefundAccountMismatch");
+      }
+
+      const sponsorBefore = await provider.connection.getBalance(sponsor.publicKey);
+      await cancelGame(await refundAccounts(gamePda));
+      await releaseActiveGames(gamePda);
+      const sponsorAfter = await provider.connection.getBalance(sponsor.publicKey);
+
+      expect(sponsorAfter - sponsorBefore).to.equal(0.1 * LAMPORTS_PER_SOL);
+      expect(await provider.connection.getBalance(sponsored.publicKey)).to.equal(0);
+    });
+  });
+
+  describe("Session Keys", () => {
+    const sessionKey = Keypair.generate();
+    let sessionPda: PublicKey;
+
+    before(() => {
+      [sessionPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("session_key"), player1Keypair.publicKey.toBuffer()],
+        program.programId
+      );
+    });
+
+    const registerSessionKey = (key: PublicKey, gameIds: anchor.BN[], expiresInSecs: number) =>
+      program.methods
+        .registerSessionKey(key, gameIds, new anchor.BN(expiresInSecs))
+        .accounts({
+          session: sessionPda,
+          player: player1Keypair.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([player1Keypair])
+        .rpc();
+
+    it("Player registers a session key scoped to specific games", async () => {
+      await registerSessionKey(sessionKey.publicKey, [new anchor.BN(1), new anchor.BN(2)], 3600);
+
+      const session = await program.account.sessionKey.fetch(sessionPda);
+      expect(session.player.toString()).to.equal(player1Keypair.publicKey.toString());
+      expect(session.sessionKey.toString()).to.equal(sessionKey.publicKey.toString());
+      expect(session.gameIds.map((id) => id.toNumber())).to.deep.equal([1, 2, 0, 0]);
+      expect(session.expiresAt.toNumber()).to.be.greaterThan(Date.now() / 1000);
+    });
+
+    it("Unscoped, long-lived or self-referencing session keys are rejected", async () => {
+      for (const [key, gameIds, expiresInSecs] of [
+        [sessionKey.publicKey, [], 3600],
+        [sessionKey.publicKey, [new anchor.BN(0)], 3600],
+        [sessionKey.publicKey, [new anchor.BN(1)], 2 * 24 * 60 * 60],
+        [player1Keypair.publicKey, [new anchor.BN(1)], 3600],
+      ] as [PublicKey, anchor.BN[], number][]) {
+        try {
+          await registerSessionKey(key, gameIds, expiresInSecs);
+          expect.fail("Should have failed with InvalidSessionKey");
+        } catch (error) {
+          expect(error.toString()).to.include("InvalidSessionKey");
+        }
+      }
+    });
+
+    it("A session key cannot revoke the player's session", async () => {
+      try {
+        await program.methods
+          .revokeSessionKey()
+          .accounts({
+            session: sessionPda,
+            player: sessionKey.publicKey,
+          })
+          .signers([sessionKey])
+          .rpc();
+        expect.fail("Should have failed with a seeds constraint");
+      } catch (error) {
+        expect(error.toString()).to.include("ConstraintSeeds");
+      }
+    });
+
+    it("Player revokes the session key and gets the rent back", async () => {
+      await program.methods
+        .revokeSessionKey()
+        .accounts({
+          session: sessionPda,
+          player: player1Keypair.publicKey,
+        })
+        .signers([player1Keypair])
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(sessionPda)).to.be.null;
+    });
+  });
+
+  describe("Rematches", () => {
+    let rematchId: anchor.BN;
+    let rematchPda: PublicKey;
+
+    const createRematch = async (creator: Keypair) => {
+      const game1 = await program.account.game.fetch(game1Pda);
+      const config = await program.account.gameConfig.fetch(gameConfigPda);
+      rematchId = config.nextGameId;
+      [rematchPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("game"), rematchId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      return program.methods
+        .createRematch(game1.id)
+        .accounts({
+          game: rematchPda,
+          previousGame: game1Pda,
+          gameConfig: gameConfigPda,
+          creator: creator.publicKey,
+          payer: creator.publicKey,
+          creatorProfile: profilePda(creator.publicKey),
+          blocked: blockedPda(creator.publicKey),
+          referrerProfile: null,
+          playerBalance: null,
+          vault: vaultPda,
+          feeReceiver: feeReceiverKeypair.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([creator])
+        .rpc();
+    };
+
+    const joinRematch = (player: Keypair) =>
+      program.methods
+        .joinGame(rematchId, null)
+        .accounts({
+          game: rematchPda,
+          bracket: null,
+          standings: null,
+          gameConfig: gameConfigPda,
+          player: player.publicKey,
+          payer: player.publicKey,
+          playerProfile: profilePda(player.publicKey),
+          blocked: blockedPda(player.publicKey),
+          referrerProfile: null,
+          playerBalance: null,
+          vault: vaultPda,
+          feeReceiver: feeReceiverKeypair.publicKey,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+
+    it("Only a player of the completed 1v1 can open a rematch", async () => {
+      try {
+        await createRematch(player3Keypair);
+        expect.fail("Should have failed with RematchNotAvailable");
+      } catch (error) {
+        expect(error.toString()).to.include("RematchNotAvailable");
+      }
+    });
+
+    it("The loser opens a rematch reserved for the winner", async () => {
+      await createRematch(player2Keypair);
+
+      const game1 = await program.account.game.fetch(game1Pda);
+      const rematch = await program.account.game.fetch(rematchPda);
+      expect(rematch.gameType).to.deep.equal({ oneVsOne: {} });
+      expect(rematch.tier).to.deep.equal(game1.tier);
+      expect(rematch.reservedFor.toString()).to.equal(player1Keypair.publicKey.toString());
+      expect(rematch.previousGameId.toString()).to.equal(game1.id.toString());
+      expect(game1.rematchGameId.toString()).to.equal(rematchId.toString());
+    });
+
+    it("Only the previous opponent can take the reserved seat", async () => {
+      try {
+        await joinRematch(player3Keypair);
+        expect.fail("Should have failed with GameReserved");
+      } catch (error) {
+        expect(error.toString()).to.include("GameReserved");
+      }
+
+      await joinRematch(player1Keypair);
+
+      const rematch = await program.account.game.fetch(rematchPda);
+      expect(rematch.players.length).to.equal(2);
+      expect(rematch.status).to.deep.equal({ active: {} });
+    });
+
+    it("A game can only be rematched once", async () => {
+      try {
+        await createRematch(player1Keypair);
+        expect.fail("Should have failed with RematchNotAvailable");
+      } catch (error) {
+        expect(error.toString()).to.include("RematchNotAvailable");
+      }
+    });
+
+    it("Cancelling a rematch frees the previous game for another one", async () => {
+      const cancelRematch = async (previousGame: PublicKey | null) =>
+        program.methods
+          .adminCancelGame(rematchId, new anchor.BN(0))
+          .accounts({
+            game: rematchPda,
+            bracket: null,
+            standings: null,
+            gameConfig: gameConfigPda,
+            adminActionLog: adminActionLogPda,
+            admin: adminKeypair.publicKey,
+            vault: vaultPda,
+            gasReimbursement: feeReceiverKeypair.publicKey,
+            rentPayer: null,
+            previousGame,
+          })
+          .remainingAccounts(await refundAccounts(rematchPda))
+          .signers([adminKeypair])
+          .rpc();
+
+      try {
+        await cancelRematch(null);
+        expect.fail("Should have failed with MissingPreviousGame");
+      } catch (error) {
+        expect(error.toString()).to.include("MissingPreviousGame");
+      }
+
+      await cancelRematch(game1Pda);
+      await releaseActiveGames(rematchPda);
+
+      const game1 = await program.account.game.fetch(game1Pda);
+      expect(game1.rematchGameId.toString()).to.equal("0");
+    });
+  });
+
+  describe("Player Balances", () => {
+    const player = Keypair.generate();
+    const COOLDOWN_SECS = 3600;
+    let gameId: anchor.BN;
+    let gamePda: PublicKey;
+
+    const available = async () => {
+      const rent = await provider.connection.getMinimumBalanceForRentExemption(8 + 32 + 8 + 1);
+      return (await provider.connection.getBalance(balancePda(player.publicKey))) - rent;
+    };
+
+    const withdrawBalance = (amount: number) =>
+      program.methods
+        .withdrawBalance(new anchor.BN(amount))
+        .accounts({
+          playerBalance: balancePda(player.publicKey),
+          gameConfig: gameConfigPda,
+          player: player.publicKey,
+        })
+        .signers([player])
+        .rpc();
+
+    const setCooldown = (balanceWithdrawalCooldownSecs: number) =>
+      applyConfigChange({ balanceWithdrawalCooldownSecs });
+
+    before(async () => {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(player.publicKey, 2 * LAMPORTS_PER_SOL)
+      );
+    });
+
+    it("Player deposits into their internal balance", async () => {
+      await program.methods
+        .depositBalance(new anchor.BN(LAMPORTS_PER_SOL))
+        .accounts({
+          playerBalance: balancePda(player.publicKey),
+          gameConfig: gameConfigPda,
+          player: player.publicKey,
+          blocked: blockedPda(player.publicKey),
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+
+      expect(await available()).to.equal(LAMPORTS_PER_SOL);
+    });
+
+    it("Entries paid from the balance debit it instead of the wallet", async () => {
+      ({ gameId, gamePda } = await createOneVsOne(player, player, {
+        playerBalance: balancePda(player.publicKey),
+      }));
+
+      const game = await program.account.game.fetch(gamePda);
+      const terms = game.stakeTerms[0];
+      expect(terms.fromBalance).to.equal(true);
+      expect(terms.payer.toString()).to.equal(balancePda(player.publicKey).toString());
+
+      const fee = Math.floor((0.1 * LAMPORTS_PER_SOL * terms.feeBps) / 10000);
+      expect(await available()).to.equal(LAMPORTS_PER_SOL - 0.1 * LAMPORTS_PER_SOL - fee);
+    });
+
+    it("Refunds of those entries are credited back to the balance", async () => {
+      const before = await available();
+
+      await program.methods
+        .adminCancelGame(gameId, new anchor.BN(0))
+        .accounts({
+          game: gamePda,
+          bracket: null,
+          standings: null,
+          gameConfig: gameConfigPda,
+          adminActionLog: adminActionLogPda,
+          admin: adminKeypair.publicKey,
+          vault: vaultPda,
+          gasReimbursement: feeReceiverKeypair.publicKey,
+          rentPayer: null,
+          previousGame: null,
+        })
+        .remainingAccounts(await refundAccounts(gamePda))
+        .signers([adminKeypair])
+        .rpc();
+      await releaseActiveGames(gamePda);
+
+      expect((await available()) - before).to.equal(0.1 * LAMPORTS_PER_SOL);
+    });
+
+    it("Withdrawals cannot exceed the balance", async () => {
+      try {
+        await withdrawBalance(2 * LAMPORTS_PER_SOL);
+        expect.fail("Should have failed with InsufficientFunds");
+      } catch (error) {
+        expect(error.toString()).to.include("InsufficientFunds");
+      }
+    });
+
+    it("Withdrawals wait out the configured cooldown", async () => {
+      await setCooldown(COOLDOWN_SECS);
+
+      try {
+        await withdrawBalance(1000);
+        expect.fail("Should have failed with BalanceCooldownActive");
+      } catch (error) {
+        expect(error.toString()).to.include("BalanceCooldownActive");
       }
+
+      await setCooldown(0);
+
+      const amount = await available();
+      const walletBefore = await provider.connection.getBalance(player.publicKey);
+      await withdrawBalance(amount);
+
+      expect(await available()).to.equal(0);
+      expect(await provider.connection.getBalance(player.publicKey)).to.be.greaterThan(walletBefore);
     });
   });
 });
\ No newline at end of file